@@ -8,27 +8,140 @@ mod uicomponent;
 mod command;
 mod size;
 mod commandbar;
+mod overlay;
+mod fuzzy;
+mod files;
+mod explorer;
+mod appdirs;
+mod recent;
+mod history;
+mod excommand;
+mod config;
+mod vim;
+mod killring;
+mod macros;
+mod unicode;
+mod theme;
 
-use command::{edit::EditCommand, system::SystemCommand, Command};
+use command::{
+    action::{Action, ACTIONS},
+    edit::EditCommand,
+    keymap::{self, KeyChord, KeyLookup, KeyMode, Keymap},
+    kill::KillCommand,
+    system::SystemCommand,
+    Command,
+};
 use commandbar::CommandBar;
-use crossterm::event::{self, Event, KeyEvent, KeyEventKind};
+use config::{Config, EditingMode};
+use crossterm::event::{self, Event, KeyEventKind};
+use excommand::{Address, ExCommand, LineRange};
+use explorer::{ExplorerAction, FileExplorer, EXPLORER_WIDTH};
 use messagebar::MessageBar;
-use position::Position;
+use overlay::{Anchor, Dialog, Dropdown, Overlay, OverlayResponse, Picker, Tooltip};
+use position::{Location, Position};
+use recent::RecentFiles;
+use history::PromptHistory;
+use killring::KillRing;
+use macros::{Macros, Repeat};
 use size::Size;
 use statusbar::StatusBar;
 use uicomponent::UiComponent;
 use std::io::Error;
+use std::ops::Range;
 use std::panic;
-use terminal::Terminal;
-use view::View;
-use simplelog::{WriteLogger, LevelFilter, Config};
-use std::fs::File;
+use terminal::{CaretShape, Terminal};
+use unicode::CharacterQuery;
+use view::{DirectoryChange, DirectoryListing, View};
+use vim::{Vim, VimMode, VimResponse};
+use simplelog::{WriteLogger, LevelFilter};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 use log::error;
 
 pub const NAME: &str = env!("CARGO_PKG_NAME");
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-const QUIT_TIMES: u8 = 3;
+// Keeps a mistyped universal argument from running an action for ages
+const MAX_REPEAT: usize = 10_000;
+
+// What to do once the open overlay gets accepted
+enum OverlayPurpose {
+    Inform,
+    ConfirmOverwrite(String),
+    OpenFile(Vec<String>),
+    ConfirmDiscard(String),
+    ConfirmDelete(PathBuf),
+    ConfirmReplace(PathBuf, PathBuf),
+    ApplyDirectoryChanges(Vec<DirectoryChange>),
+    CompletePath(Vec<String>),
+    RunAction(Vec<&'static Action>),
+    InsertCharacter(Vec<char>),
+}
+
+// What to do with the value entered in the command bar
+enum PromptPurpose {
+    SaveAs,
+    Open,
+    CreateFile(PathBuf),
+    RenameFile(PathBuf),
+    ExCommand,
+    GotoLine,
+    InsertCharacter,
+}
+
+impl PromptPurpose {
+    // Prompts of the same kind share their history. The editor has no search prompt yet; once it does, searches
+    // get a kind of their own.
+    const fn history_kind(&self) -> &'static str {
+        match self {
+            Self::SaveAs => "save-as",
+            Self::Open => "open",
+            Self::CreateFile(_) => "new-file",
+            Self::RenameFile(_) => "rename",
+            Self::ExCommand => "command",
+            Self::GotoLine => "goto",
+            Self::InsertCharacter => "character",
+        }
+    }
+
+    // The directory paths typed into the prompt are relative to
+    fn path_base(&self) -> PathBuf {
+        match self {
+            Self::SaveAs | Self::Open | Self::ExCommand | Self::GotoLine | Self::InsertCharacter => PathBuf::new(),
+            Self::CreateFile(directory) => directory.clone(),
+            Self::RenameFile(path) => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        }
+    }
+}
+
+// How often to repeat the next action: 4 after `universal-argument`, 4 times as often after each
+// further one, or whatever the digits typed right after it say
+#[derive(Clone, Copy)]
+struct UniversalArgument {
+    count: usize,
+    typed: bool,
+}
+
+impl Default for UniversalArgument {
+    fn default() -> Self {
+        Self { count: 4, typed: false }
+    }
+}
+
+impl UniversalArgument {
+    const fn repeated(self) -> Self {
+        if self.typed {
+            self
+        } else {
+            Self { count: self.count.saturating_mul(4), typed: false }
+        }
+    }
+
+    fn push_digit(&mut self, digit: usize) {
+        self.count = if self.typed { self.count.saturating_mul(10).saturating_add(digit) } else { digit };
+        self.typed = true;
+    }
+}
 
 #[derive(Default)]
 pub struct Editor {
@@ -36,7 +149,26 @@ pub struct Editor {
     view: View,
     status_bar: StatusBar,
     message_bar: MessageBar,
-    command_bar: Option<CommandBar>,
+    command_bar: Option<(CommandBar, PromptPurpose)>,
+    explorer: Option<FileExplorer>,
+    overlay: Option<(Box<dyn Overlay>, OverlayPurpose)>,
+    recent_files: RecentFiles,
+    prompt_history: PromptHistory,
+    config: Config,
+    keymap: Keymap,
+    pending_keys: Vec<KeyChord>,
+    vim: Option<Vim>,
+    kill_ring: KillRing,
+    mark: Option<Location>,
+    // whether the previous command killed text, so the next kill extends the same kill ring entry
+    kill_continues: bool,
+    // the text the previous command yanked, which yank-pop replaces
+    last_yank: Option<(Location, Location)>,
+    universal_argument: Option<UniversalArgument>,
+    macros: Macros,
+    // the macros being played, innermost last, so that a macro can't play itself
+    playing_macros: Vec<Option<String>>,
+    caret_shape: CaretShape,
     terminal_size: Size,
     title: String,
     quit_times: u8,
@@ -44,22 +176,53 @@ pub struct Editor {
 
 impl Editor {
     pub fn new() -> Result<Self, Error> {
-        Self::initialize_logger();
+        let (mut config, mut errors) = Config::load();
+        let (args, args_errors) = config.apply_args(std::env::args().skip(1));
+        errors.extend(args_errors);
+        errors.extend(Self::initialize_logger(&config));
         Self::set_panic_printing();
         Terminal::initialize()?;
 
         let mut editor = Self::default();
         let size = Terminal::size().unwrap_or_default();
         editor.resize(size);
+        editor.config = config;
+        errors.extend(editor.apply_config());
 
-        editor.message_bar
-            .update_message("HELP: Ctrl-S = save | Ctrl-Q = quit".to_string());
+        if errors.is_empty() {
+            editor.message_bar.update_message("HELP: Ctrl-S = save | Ctrl-O = open | Ctrl-Q = quit".to_string());
+        } else {
+            for err in &errors {
+                error!("{err}");
+            }
+            editor.message_bar.update_message(format!("ERR: {}", errors.join("; ")));
+        }
+
+        editor.recent_files = RecentFiles::load();
+        editor.prompt_history = PromptHistory::load();
+        editor.macros = Macros::load();
+        editor.view.set_recent_files(editor.recent_files.display_paths());
 
-        let args: Vec<String> = std::env::args().collect();
-        if let Some(file_name) = args.get(1) {
-            if editor.view.load(file_name).is_err() {
-                editor.message_bar
-                    .update_message(format!("ERR: Could not open file: {file_name}"));
+        // `+line file` and `file:line:col` open the file at that position
+        let (file_arg, line_arg) = match args.as_slice() {
+            [line, file, ..] if line.starts_with('+') => (Some(file.as_str()), Some(&line[1..])),
+            [file, ..] => (Some(file.as_str()), None),
+            [] => (None, None),
+        };
+        if let Some(file_arg) = file_arg {
+            let (file_name, position) = files::split_position(file_arg);
+            if Path::new(file_name).is_dir() {
+                editor.show_explorer(Path::new(file_name));
+            } else {
+                editor.load(file_name);
+                let position = match line_arg {
+                    Some("") => Some((Address::Last, None)),
+                    Some(line) => excommand::parse_position(line).ok(),
+                    None => position.map(|(line, column)| (Address::Line(line), column)),
+                };
+                if let Some((address, column)) = position {
+                    editor.jump_to(address, column);
+                }
             }
         }
 
@@ -69,11 +232,30 @@ impl Editor {
 
     fn resize(&mut self, size: Size) {
         self.terminal_size = size;
+        let content_area = self.content_area();
+
+        let explorer_width = if self.explorer.is_some() {
+            EXPLORER_WIDTH.min(size.width / 2)
+        } else {
+            0
+        };
+        self.view.set_origin_col(explorer_width);
         self.view.resize(Size {
-            height: size.height.saturating_sub(2),
-            width: size.width,
+            height: content_area.height,
+            width: size.width.saturating_sub(explorer_width),
         });
 
+        if let Some(explorer) = self.explorer.as_mut() {
+            explorer.resize(Size {
+                height: content_area.height,
+                width: explorer_width,
+            });
+        }
+
+        if let Some((overlay, _)) = self.overlay.as_mut() {
+            overlay.resize(content_area);
+        }
+
         self.message_bar.resize(Size {
             height: 1,
             width: size.width,
@@ -84,7 +266,7 @@ impl Editor {
             width: size.width,
         });
 
-        if let Some(command_bar) = self.command_bar.as_mut() {
+        if let Some((command_bar, _)) = self.command_bar.as_mut() {
             command_bar.resize(Size {
                 height: 1,
                 width: size.width,
@@ -92,8 +274,20 @@ impl Editor {
         }
     }
 
+    // The area above the status bar, shared by the view and any panels and overlays
+    fn content_area(&self) -> Size {
+        Size {
+            height: self.terminal_size.height.saturating_sub(2),
+            width: self.terminal_size.width,
+        }
+    }
+
     pub fn refresh_status(&mut self) {
-        let status = self.view.get_status();
+        let mut status = self.view.get_status();
+        status.mode = self.vim.as_ref().map(|vim| vim.mode().label().to_string());
+        if self.macros.is_recording() {
+            status.mode = Some(status.mode.map_or_else(|| "REC".to_string(), |mode| format!("{mode} REC")));
+        }
         let title = format!("{} - {NAME}", status.file_name);
         self.status_bar.update_status(status);
 
@@ -102,12 +296,67 @@ impl Editor {
         }
     }
 
-    fn initialize_logger() {
-        WriteLogger::init(
-            LevelFilter::Debug,
-            Config::default(),
-            File::create("editor.log").unwrap(),
-        ).unwrap();
+    // Starts logging to the configured file, returning what went wrong if it can't be written
+    fn initialize_logger(config: &Config) -> Option<String> {
+        if config.log_level == LevelFilter::Off {
+            return None;
+        }
+
+        let file = match File::create(&config.log_file) {
+            Ok(file) => file,
+            Err(err) => return Some(format!("Could not create log file {}: {err}", config.log_file.display())),
+        };
+        WriteLogger::init(config.log_level, simplelog::Config::default(), file).err().map(|err| err.to_string())
+    }
+
+    // Applies the key bindings from the config on top of those of the editing mode,
+    // returning what was wrong with the invalid ones
+    fn bind_keys(&mut self) -> Vec<String> {
+        let profile = match self.config.editing_mode {
+            EditingMode::Emacs => keymap::EMACS_BINDINGS,
+            EditingMode::Default | EditingMode::Vim => &[],
+        };
+        self.keymap = Keymap::new(profile);
+
+        let mut errors = Vec::new();
+        for binding in &self.config.key_bindings {
+            if let Err(err) = self.keymap.bind(binding.mode.as_deref(), &binding.keys, &binding.action) {
+                errors.push(format!("keys: {}: {err}", binding.keys));
+            }
+        }
+        errors
+    }
+
+    // Passes the settings on to the components that use them, returning what was wrong with the key bindings
+    fn apply_config(&mut self) -> Vec<String> {
+        self.message_bar.set_timeout(self.config.message_timeout);
+        self.view.set_soft_wrap(self.config.soft_wrap);
+        self.view.set_line_numbers(self.config.line_numbers);
+        self.view.set_tab_display(self.config.tab_width, self.config.show_tabs);
+        self.view.set_show_whitespace(self.config.show_whitespace);
+        self.view.set_indent_policy(self.config.indent_style);
+        self.view.set_trim_on_save(self.config.trim_on_save);
+        self.view.set_wrap_column(self.config.wrap_column, self.config.auto_wrap);
+        self.view.set_theme(self.config.theme);
+        self.status_bar.set_theme(self.config.theme);
+        if let Some(explorer) = self.explorer.as_mut() {
+            explorer.set_theme(self.config.theme);
+        }
+        if let Some((overlay, _)) = self.overlay.as_mut() {
+            overlay.set_theme(self.config.theme);
+        }
+
+        match self.config.editing_mode {
+            EditingMode::Default | EditingMode::Emacs => {
+                self.vim = None;
+                self.view.set_highlight(None);
+            }
+            EditingMode::Vim => {
+                self.vim.get_or_insert_with(Vim::default);
+            }
+        }
+        self.clear_mark();
+        self.bind_keys()
     }
 
     fn set_panic_printing() {
@@ -134,7 +383,7 @@ impl Editor {
             }
 
             match event::read() {
-                Ok(event) => self.evaluate_event(event),
+                Ok(event) => self.evaluate_event(&event),
                 Err(err) => {
                     error!("Failed to read event: {err:?}");
                     #[cfg(debug_assertions)]
@@ -148,27 +397,124 @@ impl Editor {
         }
     }
 
-    fn evaluate_event(&mut self, event: Event) {
-        let should_process = match event {
-            Event::Key(KeyEvent { kind, .. }) => kind == KeyEventKind::Press,
-            Event::Resize(_, _) => true,
-            _ => false,
-        };
+    fn evaluate_event(&mut self, event: &Event) {
+        match *event {
+            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                // macros record the keys as pressed, so playing them goes through vim mode and the keymap again
+                let chord = key_event.into();
+                self.macros.record(chord);
+                self.evaluate_key(chord);
+            }
+            Event::Resize(width, height) => self.process_command(Command::System(SystemCommand::Resize(Size {
+                height: height as usize,
+                width: width as usize,
+            }))),
+            _ => {}
+        }
+    }
 
-        if !should_process {
-            return;
+    // Looks up the keys pressed so far, waiting for more if they start a longer sequence
+    fn evaluate_key(&mut self, chord: KeyChord) {
+        if self.pending_keys.is_empty() && self.key_mode() == KeyMode::Editor {
+            if let Some(vim) = self.vim.as_mut() {
+                let response = vim.handle_key(chord, &mut self.view);
+                vim.sync_view(&mut self.view);
+
+                match response {
+                    VimResponse::Handled => return,
+                    VimResponse::PassThrough => {}
+                    VimResponse::CommandLine => {
+                        self.show_prompt(":", PromptPurpose::ExCommand, "");
+                        return;
+                    }
+                    VimResponse::Replay(keys) => {
+                        for key in keys {
+                            self.evaluate_key(key);
+                        }
+                        return;
+                    }
+                }
+            }
+        }
+
+        if let Some(argument) = self.universal_argument.as_mut().filter(|_| self.pending_keys.is_empty()) {
+            if let Some(digit) = chord.text().and_then(|c| c.to_digit(10)) {
+                argument.push_digit(digit as usize);
+                self.message_bar.update_message(format!("C-u {} -", argument.count));
+                return;
+            }
         }
 
-        if let Ok(command) = Command::try_from(event) {
-            self.process_command(command);
+        self.pending_keys.push(chord);
+
+        match self.keymap.lookup(self.key_mode(), &self.pending_keys) {
+            KeyLookup::Action(action) => {
+                // the keys that stop a recording aren't part of it
+                if action.command == Command::System(SystemCommand::RecordMacro) {
+                    self.macros.forget(self.pending_keys.len());
+                }
+                self.pending_keys.clear();
+                let count = match action.command {
+                    Command::System(SystemCommand::UniversalArgument) => 1,
+                    _ => self.take_repeat_count(),
+                };
+                for _ in 0..count {
+                    self.process_command(action.command);
+                }
+            }
+            KeyLookup::Pending => {
+                let keys = keymap::describe_sequence(&self.pending_keys);
+                self.message_bar.update_message(format!("{keys} -"));
+            }
+            KeyLookup::Unbound => {
+                let keys = std::mem::take(&mut self.pending_keys);
+                if let [chord] = keys.as_slice() {
+                    // keys that aren't shortcuts type their character
+                    if let Some(c) = chord.text() {
+                        for _ in 0..self.take_repeat_count() {
+                            self.process_command(Command::Edit(EditCommand::Insert(c)));
+                        }
+                    }
+                } else {
+                    let keys = keymap::describe_sequence(&keys);
+                    self.message_bar.update_message(format!("{keys} is not bound."));
+                }
+            }
+        }
+    }
+
+    // How often to run the next action, as given by the universal argument before it
+    fn take_repeat_count(&mut self) -> usize {
+        self.universal_argument.take().map_or(1, |argument| argument.count.min(MAX_REPEAT))
+    }
+
+    // Which keymap applies, depending on what has the focus
+    fn key_mode(&self) -> KeyMode {
+        if self.overlay.is_some() {
+            KeyMode::Overlay
+        } else if self.command_bar.is_some() {
+            KeyMode::Prompt
+        } else if self.explorer.as_ref().is_some_and(FileExplorer::is_focused) {
+            KeyMode::Explorer
+        } else {
+            KeyMode::Editor
         }
     }
 
     fn process_command(&mut self, command: Command) {
+        let kill_continues = std::mem::take(&mut self.kill_continues);
+        let last_yank = self.last_yank.take();
+
+        if !matches!(command, Command::System(SystemCommand::Resize(_)))
+            && (!self.process_overlay_command(command) || !self.process_explorer_command(command))
+        {
+            return;
+        }
+
         match command {
             Command::System(SystemCommand::Quit) => {
                 if self.command_bar.is_none()  {
-                    self.handle_quit()
+                    self.handle_quit();
                 }
             },
             Command::System(SystemCommand::Resize(size)) => self.resize(size),
@@ -177,69 +523,967 @@ impl Editor {
 
         match command {
             Command::System(SystemCommand::Quit | SystemCommand::Resize(_)) => {} // already handled above
-            Command::System(SystemCommand::Save) => {
-                if self.command_bar.is_none() {
-                    self.handle_save();
+            Command::System(SystemCommand::Dismiss) => {
+                if let Some((_, purpose)) = self.dismiss_prompt() {
+                    let message = match purpose {
+                        PromptPurpose::SaveAs => "Save aborted.",
+                        PromptPurpose::Open
+                        | PromptPurpose::CreateFile(_)
+                        | PromptPurpose::RenameFile(_)
+                        | PromptPurpose::ExCommand
+                        | PromptPurpose::GotoLine
+                        | PromptPurpose::InsertCharacter => "Cancelled.",
+                    };
+                    self.message_bar.update_message(message.to_string());
+                } else {
+                    self.clear_mark();
                 }
             },
-            Command::System(SystemCommand::Dismiss) => {
-                if self.command_bar.is_some() {
-                    self.dismiss_prompt();
-                    self.message_bar.update_message("Save aborted.".to_string());
+            Command::System(system_command) => {
+                if self.command_bar.is_none() {
+                    self.process_system_command(system_command);
                 }
             },
             Command::Edit(edit_command) => {
-                if let Some(command_bar) = &mut self.command_bar {
+                if let Some((command_bar, purpose)) = &mut self.command_bar {
                     if matches!(edit_command, EditCommand::InsertNewline) {
-                        let file_name = command_bar.value();
-                        self.dismiss_prompt();
-                        self.save(Some(&file_name));
+                        let value = command_bar.value();
+                        if let Some((_, purpose)) = self.dismiss_prompt() {
+                            self.submit_prompt(purpose, value);
+                        }
+                    } else if matches!(edit_command, EditCommand::Insert('\t')) {
+                        let value = command_bar.value_before_caret();
+                        let completion = match purpose {
+                            PromptPurpose::ExCommand => excommand::complete(&value),
+                            PromptPurpose::GotoLine | PromptPurpose::InsertCharacter => return,
+                            _ => files::complete_path(&value, &purpose.path_base()),
+                        };
+                        self.complete_prompt(completion);
                     } else {
                         command_bar.handle_edit_command(edit_command);
                     }
                 } else {
+                    // reflow works on the selected lines, so the mark goes after the edit
                     self.view.handle_edit_command(edit_command);
+                    self.clear_mark();
                 }
             },
             Command::Move(move_command) => {
-                if self.command_bar.is_none() {
+                if let Some((command_bar, _)) = &mut self.command_bar {
+                    command_bar.handle_move_command(move_command);
+                } else {
                     self.view.handle_move_command(move_command);
                 }
             },
+            Command::Kill(kill_command) => {
+                if self.key_mode() == KeyMode::Editor {
+                    self.process_kill_command(kill_command, kill_continues, last_yank);
+                }
+            }
+        }
+
+        if let Some(mark) = self.mark {
+            let caret = self.view.location();
+            self.view.set_highlight(Some((mark.min(caret), mark.max(caret))));
+        }
+    }
+
+    fn process_kill_command(&mut self, command: KillCommand, kill_continues: bool, last_yank: Option<(Location, Location)>) {
+        let caret = self.view.location();
+
+        match command {
+            KillCommand::SetMark => {
+                self.mark = Some(caret);
+                self.message_bar.update_message("Mark set".to_string());
+            }
+            KillCommand::KillLine => {
+                let line_length = self.view.grapheme_count(caret.row);
+                let end = if caret.grapheme_index < line_length {
+                    Location { grapheme_index: line_length, row: caret.row }
+                } else if caret.row + 1 < self.view.line_count() {
+                    Location { grapheme_index: 0, row: caret.row + 1 }
+                } else {
+                    self.message_bar.update_message("End of file".to_string());
+                    return;
+                };
+                self.kill(caret, end, kill_continues);
+            }
+            KillCommand::KillRegion | KillCommand::CopyRegion => {
+                let Some(mark) = self.mark else {
+                    self.message_bar.update_message("The mark is not set, so there is no region.".to_string());
+                    return;
+                };
+                self.clear_mark();
+
+                let (start, end) = (mark.min(caret), mark.max(caret));
+                if command == KillCommand::KillRegion {
+                    self.kill(start, end, kill_continues);
+                } else {
+                    self.kill_ring.kill(self.view.text_between(start, end), kill_continues);
+                    self.message_bar.update_message("Copied the region".to_string());
+                }
+            }
+            KillCommand::Yank => {
+                self.clear_mark();
+                match self.kill_ring.yank().map(str::to_string) {
+                    Some(text) => {
+                        self.view.insert_text(caret, &text);
+                        self.last_yank = Some((caret, self.view.location()));
+                    }
+                    None => self.message_bar.update_message("The kill ring is empty.".to_string()),
+                }
+            }
+            KillCommand::YankPop => {
+                let Some((start, end)) = last_yank else {
+                    self.message_bar.update_message("The previous command was not a yank.".to_string());
+                    return;
+                };
+                if let Some(text) = self.kill_ring.yank_pop().map(str::to_string) {
+                    self.view.delete_between(start, end);
+                    self.view.insert_text(start, &text);
+                    self.last_yank = Some((start, self.view.location()));
+                }
+            }
+        }
+    }
+
+    // Presses the keys of the macro saved under the name, or of the last recorded one
+    fn play_macro(&mut self, name: Option<&str>, repeat: Repeat) {
+        let Some(keys) = self.macros.get(name) else {
+            let err = name.map_or_else(|| "No macro has been recorded".to_string(), |name| format!("No macro named {name}"));
+            self.message_bar.update_message(format!("ERR: {err}"));
+            return;
+        };
+        let name = name.map(str::to_string);
+        if self.playing_macros.contains(&name) {
+            let err = name.map_or_else(|| "The last macro plays itself".to_string(), |name| format!("Macro {name} plays itself"));
+            self.message_bar.update_message(format!("ERR: {err}"));
+            return;
+        }
+        self.playing_macros.push(name);
+
+        let times = match repeat {
+            Repeat::Times(times) => times.min(MAX_REPEAT),
+            Repeat::ToEnd => MAX_REPEAT,
+        };
+        for _ in 0..times {
+            let row = self.view.location().row;
+            // the caret can sit below the last line, where there is nothing left to process
+            if matches!(repeat, Repeat::ToEnd) && row >= self.view.line_count() {
+                break;
+            }
+            for key in &keys {
+                self.evaluate_key(*key);
+            }
+            if self.should_quit || (matches!(repeat, Repeat::ToEnd) && self.view.location().row <= row) {
+                break;
+            }
+        }
+        self.playing_macros.pop();
+    }
+
+    // Cuts the text into the kill ring, adding it to the last entry if the previous command killed text too
+    fn kill(&mut self, start: Location, end: Location, append: bool) {
+        let text = self.view.text_between(start, end);
+        self.view.delete_between(start, end);
+        self.kill_ring.kill(text, append);
+        self.kill_continues = true;
+    }
+
+    fn clear_mark(&mut self) {
+        if self.mark.take().is_some() {
+            self.view.set_highlight(None);
+        }
+    }
+
+    // Handles the system commands that only apply while no prompt is open
+    fn process_system_command(&mut self, command: SystemCommand) {
+        match command {
+            SystemCommand::Save => self.handle_save(),
+            SystemCommand::SaveAs => {
+                let current = self
+                    .view
+                    .file_path()
+                    .map_or_else(String::new, |path| path.to_string_lossy().to_string());
+                self.show_prompt("Save as: ", PromptPurpose::SaveAs, &current);
+            }
+            SystemCommand::Reload => self.reload(),
+            SystemCommand::CommandPalette => self.show_command_palette(),
+            SystemCommand::CommandLine => self.show_prompt(":", PromptPurpose::ExCommand, ""),
+            SystemCommand::KeyBindings => self.show_key_bindings(),
+            SystemCommand::InspectCharacter => self.show_character_info(),
+            SystemCommand::InsertCharacter => {
+                self.show_prompt(
+                    "Insert character (digraph first, e.g. 12 for ½, then U+hex or name): ",
+                    PromptPurpose::InsertCharacter,
+                    "",
+                );
+            }
+            SystemCommand::FindFile => self.show_file_finder(),
+            SystemCommand::ToggleExplorer => self.toggle_explorer(),
+            SystemCommand::Open => self.show_prompt("Open: ", PromptPurpose::Open, ""),
+            SystemCommand::ToggleSoftWrap => {
+                self.config.soft_wrap = !self.config.soft_wrap;
+                self.view.set_soft_wrap(self.config.soft_wrap);
+                let state = if self.config.soft_wrap { "on" } else { "off" };
+                self.message_bar.update_message(format!("Soft wrap {state}"));
+            }
+            SystemCommand::ToggleWhitespace => {
+                self.config.show_whitespace = !self.config.show_whitespace;
+                self.view.set_show_whitespace(self.config.show_whitespace);
+                let state = if self.config.show_whitespace { "on" } else { "off" };
+                self.message_bar.update_message(format!("Visible whitespace {state}"));
+            }
+            SystemCommand::GotoLine => {
+                self.show_prompt("Go to line[:column], +/-offset or percent%: ", PromptPurpose::GotoLine, "");
+            }
+            SystemCommand::RecentFiles => self.show_recent_files(),
+            SystemCommand::UniversalArgument => {
+                let argument = self.universal_argument.map_or_else(UniversalArgument::default, UniversalArgument::repeated);
+                self.universal_argument = Some(argument);
+                self.message_bar.update_message(format!("C-u {} -", argument.count));
+            }
+            SystemCommand::RecordMacro => {
+                if self.macros.is_recording() {
+                    let message = match self.macros.stop_recording() {
+                        0 => "Nothing recorded.".to_string(),
+                        1 => "Recorded a macro of 1 key.".to_string(),
+                        len => format!("Recorded a macro of {len} keys."),
+                    };
+                    self.message_bar.update_message(message);
+                } else {
+                    self.macros.start_recording();
+                    self.message_bar.update_message("Recording a macro...".to_string());
+                }
+            }
+            SystemCommand::PlayMacro => self.play_macro(None, Repeat::Times(1)),
+            SystemCommand::Quit | SystemCommand::Resize(_) | SystemCommand::Dismiss => {}
+        }
+    }
+
+    // Lets the open overlay react to the command first.
+    // Returns whether the editor should still process the command itself.
+    fn process_overlay_command(&mut self, command: Command) -> bool {
+        let Some((overlay, _)) = self.overlay.as_mut() else {
+            return true;
+        };
+
+        match overlay.handle_command(command) {
+            OverlayResponse::Handled => false,
+            OverlayResponse::Closed => {
+                self.close_overlay();
+                true
+            }
+            OverlayResponse::Accepted(index) => {
+                if let Some(purpose) = self.close_overlay() {
+                    self.accept_overlay(purpose, index);
+                }
+                false
+            }
+            OverlayResponse::Cancelled => {
+                self.close_overlay();
+                false
+            }
+        }
+    }
+
+    // Sends navigation and editing commands to the explorer while it has the focus.
+    // Returns whether the editor should still process the command itself.
+    fn process_explorer_command(&mut self, command: Command) -> bool {
+        if self.command_bar.is_some() {
+            return true;
+        }
+        let Some(explorer) = self.explorer.as_mut().filter(|explorer| explorer.is_focused()) else {
+            return true;
+        };
+        if !matches!(
+            command,
+            Command::Move(_) | Command::Edit(_) | Command::System(SystemCommand::Dismiss)
+        ) {
+            return true;
+        }
+
+        match explorer.handle_command(command) {
+            ExplorerAction::None => {}
+            ExplorerAction::Open(path) => {
+                explorer.set_focused(false);
+                self.open_file(path.to_string_lossy().to_string());
+            }
+            ExplorerAction::Create(directory) => {
+                self.show_prompt("New file (end with / for a directory): ", PromptPurpose::CreateFile(directory), "");
+            }
+            ExplorerAction::Rename(path) => {
+                let name = path
+                    .file_name()
+                    .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+                self.show_prompt("Rename to: ", PromptPurpose::RenameFile(path), &name);
+            }
+            ExplorerAction::Delete(path) => {
+                let what = if path.is_dir() { "the directory" } else { "the file" };
+                let dialog = Dialog::confirm(
+                    "Delete?",
+                    vec![format!("Delete {what} {}?", path.display()), "This cannot be undone.".to_string()],
+                );
+                self.open_overlay(Box::new(dialog), OverlayPurpose::ConfirmDelete(path));
+            }
+            ExplorerAction::EditDirectory(directory) => {
+                explorer.set_focused(false);
+                self.edit_directory(&directory);
+            }
+            ExplorerAction::Unfocus => explorer.set_focused(false),
+        }
+        false
+    }
+
+    fn toggle_explorer(&mut self) {
+        match self.explorer.as_mut() {
+            Some(explorer) if explorer.is_focused() => {
+                self.explorer = None;
+                self.resize(self.terminal_size);
+            }
+            Some(explorer) => explorer.set_focused(true),
+            None => {
+                let root = self
+                    .view
+                    .file_path()
+                    .and_then(Path::parent)
+                    .filter(|parent| !parent.as_os_str().is_empty())
+                    .map_or_else(|| std::env::current_dir().unwrap_or_default(), Path::to_path_buf);
+                self.show_explorer(&root);
+            }
+        }
+    }
+
+    fn show_explorer(&mut self, root: &Path) {
+        let mut explorer = FileExplorer::new(root);
+        explorer.set_theme(self.config.theme);
+        explorer.set_focused(true);
+        self.explorer = Some(explorer);
+        self.resize(self.terminal_size);
+    }
+
+    fn refresh_explorer(&mut self) {
+        if let Some(explorer) = self.explorer.as_mut() {
+            explorer.refresh();
+        }
+    }
+
+    fn create_file(&mut self, directory: &Path, name: &str) {
+        if name.is_empty() {
+            return;
         }
+
+        let path = directory.join(name);
+        let result = if name.ends_with('/') {
+            fs::create_dir_all(&path)
+        } else {
+            File::create_new(&path).map(|_| ())
+        };
+
+        match result {
+            Ok(()) => self.message_bar.update_message(format!("Created {}", path.display())),
+            Err(err) => self.message_bar.update_message(format!("ERR: Could not create {name}: {err}")),
+        }
+        self.refresh_explorer();
     }
 
-    fn dismiss_prompt(&mut self) {
-        self.command_bar = None;
+    // The path of the open file relative to the path, if the open file is that path or inside it
+    fn open_file_within(&self, path: &Path) -> Option<PathBuf> {
+        let open = fs::canonicalize(self.view.file_path()?).ok()?;
+        let path = fs::canonicalize(path).ok()?;
+        open.strip_prefix(path).ok().map(Path::to_path_buf)
+    }
+
+    fn rename_file(&mut self, path: &Path, new_name: &str) {
+        if new_name.is_empty() {
+            return;
+        }
+
+        let new_path = path.parent().map_or_else(|| PathBuf::from(new_name), |parent| parent.join(new_name));
+        // renaming a file to itself, e.g. only changing its case, replaces nothing
+        let is_same_file = fs::canonicalize(path)
+            .is_ok_and(|path| fs::canonicalize(&new_path).is_ok_and(|new_path| new_path == path));
+        if new_path.exists() && !is_same_file {
+            let dialog = Dialog::confirm(
+                "Replace?",
+                vec![format!("{} already exists.", new_path.display()), "Do you want to replace it?".to_string()],
+            );
+            self.open_overlay(Box::new(dialog), OverlayPurpose::ConfirmReplace(path.to_path_buf(), new_path));
+        } else {
+            self.move_file(path, &new_path);
+        }
+    }
+
+    fn move_file(&mut self, path: &Path, new_path: &Path) {
+        let moved_open_file = self.open_file_within(path);
+        let replaces_open_file = self.open_file_within(new_path).is_some();
+
+        match fs::rename(path, new_path) {
+            Ok(()) => {
+                if let Some(relative) = moved_open_file {
+                    let open = if relative.as_os_str().is_empty() {
+                        new_path.to_path_buf()
+                    } else {
+                        new_path.join(relative)
+                    };
+                    self.view.set_file_path(&open.to_string_lossy());
+                    self.message_bar.update_message(format!("Renamed to {}", new_path.display()));
+                } else if replaces_open_file {
+                    self.view.detach_file();
+                    self.message_bar.update_message(format!(
+                        "Renamed to {}, which replaced the open file. Save it under a name to keep it.",
+                        new_path.display()
+                    ));
+                } else {
+                    self.message_bar.update_message(format!("Renamed to {}", new_path.display()));
+                }
+            }
+            Err(err) => self.message_bar.update_message(format!("ERR: Could not rename: {err}")),
+        }
+        self.refresh_explorer();
+    }
+
+    fn delete_file(&mut self, path: &Path) {
+        let deletes_open_file = self.open_file_within(path).is_some();
+        let result = if path.is_dir() {
+            fs::remove_dir_all(path)
+        } else {
+            fs::remove_file(path)
+        };
+
+        match result {
+            // saving would quietly create the file again, so the buffer no longer belongs to it
+            Ok(()) if deletes_open_file => {
+                self.view.detach_file();
+                self.message_bar.update_message(format!(
+                    "Deleted {}, which was open. Save it under a name to keep it.",
+                    path.display()
+                ));
+            }
+            Ok(()) => self.message_bar.update_message(format!("Deleted {}", path.display())),
+            Err(err) => self.message_bar.update_message(format!("ERR: Could not delete: {err}")),
+        }
+        self.refresh_explorer();
+    }
+
+    fn edit_directory(&mut self, directory: &Path) {
+        if self.view.get_status().is_modified {
+            self.message_bar
+                .update_message("Save or discard the current changes before editing a directory.".to_string());
+            return;
+        }
+
+        let directory = directory.to_string_lossy();
+        if self.view.load_directory(&directory).is_err() {
+            self.message_bar
+                .update_message(format!("ERR: Could not read directory: {directory}"));
+        } else {
+            self.message_bar.update_message(
+                "Edit the names, delete lines to delete entries, add lines to create them. Ctrl-S applies.".to_string(),
+            );
+        }
+    }
+
+    // Asks for confirmation before applying what the edited directory listing describes
+    fn review_directory_changes(&mut self, changes: Result<Vec<DirectoryChange>, String>) {
+        match changes {
+            Ok(changes) if changes.is_empty() => {
+                self.message_bar.update_message("No changes to apply.".to_string());
+            }
+            Ok(changes) => {
+                let summary = changes.iter().map(ToString::to_string).collect();
+                let dialog = Dialog::confirm(&format!("Apply {} changes?", changes.len()), summary);
+                self.open_overlay(Box::new(dialog), OverlayPurpose::ApplyDirectoryChanges(changes));
+            }
+            Err(err) => self.message_bar.update_message(format!("ERR: {err}")),
+        }
+    }
+
+    fn apply_directory_changes(&mut self, changes: &[DirectoryChange]) {
+        let Some(root) = self.view.directory_root().map(Path::to_path_buf) else {
+            return;
+        };
+
+        match DirectoryListing::apply(changes) {
+            Ok(()) => self.message_bar.update_message(format!("Applied {} changes.", changes.len())),
+            Err(err) => self.message_bar.update_message(format!("ERR: Could not apply all changes: {err}")),
+        }
+
+        if self.view.load_directory(&root.to_string_lossy()).is_err() {
+            self.message_bar
+                .update_message(format!("ERR: Could not read directory: {}", root.display()));
+        }
+        self.refresh_explorer();
+    }
+
+    fn open_overlay(&mut self, mut overlay: Box<dyn Overlay>, purpose: OverlayPurpose) {
+        overlay.set_theme(self.config.theme);
+        overlay.resize(self.content_area());
+        self.overlay = Some((overlay, purpose));
+    }
+
+    fn close_overlay(&mut self) -> Option<OverlayPurpose> {
+        self.view.set_needs_redraw(true);
+        if let Some(explorer) = self.explorer.as_mut() {
+            explorer.set_needs_redraw(true);
+        }
+        self.overlay.take().map(|(_, purpose)| purpose)
+    }
+
+    fn accept_overlay(&mut self, purpose: OverlayPurpose, index: usize) {
+        match purpose {
+            OverlayPurpose::Inform => {}
+            OverlayPurpose::ConfirmOverwrite(file_name) => self.save(Some(&file_name)),
+            OverlayPurpose::OpenFile(files) => {
+                if let Some(file_name) = files.get(index) {
+                    self.open_file(file_name.clone());
+                }
+            }
+            OverlayPurpose::ConfirmDiscard(file_name) => self.load(&file_name),
+            OverlayPurpose::ConfirmDelete(path) => self.delete_file(&path),
+            OverlayPurpose::ConfirmReplace(path, new_path) => self.move_file(&path, &new_path),
+            OverlayPurpose::ApplyDirectoryChanges(changes) => self.apply_directory_changes(&changes),
+            OverlayPurpose::RunAction(actions) => {
+                if let Some(action) = actions.get(index) {
+                    self.process_command(action.command);
+                }
+            }
+            OverlayPurpose::InsertCharacter(characters) => {
+                if let Some(&c) = characters.get(index) {
+                    self.insert_character(c);
+                }
+            }
+            OverlayPurpose::CompletePath(paths) => {
+                if let (Some(path), Some((command_bar, _))) = (paths.get(index), self.command_bar.as_mut()) {
+                    command_bar.replace_before_caret(path);
+                }
+            }
+        }
+    }
+
+    fn show_file_finder(&mut self) {
+        let root = std::env::current_dir().unwrap_or_default();
+        let files = files::list_files(&root);
+        if files.is_empty() {
+            self.message_bar.update_message("No files found.".to_string());
+            return;
+        }
+
+        let picker = Picker::new("Open file", files.clone(), Some(files::preview_file));
+        self.open_overlay(Box::new(picker), OverlayPurpose::OpenFile(files));
+    }
+
+    // Opens the file in place of the current buffer, asking first if that would lose changes
+    fn open_file(&mut self, file_name: String) {
+        if self.view.get_status().is_modified {
+            let dialog = Dialog::confirm(
+                "Discard changes?",
+                vec!["The current file has unsaved changes.".to_string(), format!("Open {file_name} anyway?")],
+            );
+            self.open_overlay(Box::new(dialog), OverlayPurpose::ConfirmDiscard(file_name));
+        } else {
+            self.load(&file_name);
+        }
+    }
+
+    fn load(&mut self, file_name: &str) {
+        self.remember_location();
+        self.clear_mark();
+
+        if self.view.load(file_name).is_err() {
+            self.message_bar
+                .update_message(format!("ERR: Could not open file: {file_name}"));
+        } else {
+            if let Some(location) = self.recent_files.location_of(Path::new(file_name)) {
+                self.view.set_location(location);
+            }
+            self.remember_location();
+        }
+    }
+
+    // Puts the current file at the top of the recent files, along with where the caret is
+    fn remember_location(&mut self) {
+        if self.view.directory_root().is_some() {
+            return;
+        }
+
+        if let Some(path) = self.view.file_path() {
+            self.recent_files.remember(path, self.view.location());
+            self.view.set_recent_files(self.recent_files.display_paths());
+        }
+    }
+
+    fn show_command_palette(&mut self) {
+        let bindings: Vec<String> = ACTIONS
+            .iter()
+            .map(|action| self.keymap.bindings_for(action).join(", "))
+            .collect();
+        let name_width = ACTIONS.iter().map(|action| action.name.len()).max().unwrap_or(0);
+        let binding_width = bindings.iter().map(String::len).max().unwrap_or(0);
+
+        let entries = ACTIONS
+            .iter()
+            .zip(&bindings)
+            .map(|(action, binding)| {
+                format!("{:<name_width$}  {binding:<binding_width$}  {}", action.name, action.description)
+            })
+            .collect();
+
+        let picker = Picker::new("Command palette", entries, None);
+        self.open_overlay(Box::new(picker), OverlayPurpose::RunAction(ACTIONS.iter().collect()));
+    }
+
+    fn show_key_bindings(&mut self) {
+        let bindings = self.keymap.all_bindings();
+        let keys_width = bindings.iter().map(|(_, keys, _)| keys.len()).max().unwrap_or(0);
+
+        let entries = bindings
+            .iter()
+            .map(|(mode, keys, action)| format!("{mode:<8}  {keys:<keys_width$}  {}", action.name))
+            .collect();
+        let actions = bindings.into_iter().map(|(_, _, action)| action).collect();
+
+        let picker = Picker::new("Key bindings", entries, None);
+        self.open_overlay(Box::new(picker), OverlayPurpose::RunAction(actions));
+    }
+
+    // Reads the current file or directory from disk again, asking first if that would lose changes
+    fn reload(&mut self) {
+        if let Some(root) = self.view.directory_root().map(Path::to_path_buf) {
+            self.edit_directory(&root);
+        } else if let Some(path) = self.view.file_path() {
+            self.open_file(path.to_string_lossy().to_string());
+        } else {
+            self.message_bar.update_message("There is no file to reload.".to_string());
+        }
+    }
+
+    fn show_recent_files(&mut self) {
+        let files = self.recent_files.display_paths();
+        if files.is_empty() {
+            self.message_bar.update_message("No recent files.".to_string());
+            return;
+        }
+
+        let picker = Picker::new("Recent files", files.clone(), Some(files::preview_file));
+        self.open_overlay(Box::new(picker), OverlayPurpose::OpenFile(files));
+    }
+
+    // Puts the completion left of the prompt's caret, offering a dropdown if there is more than one way to go
+    fn complete_prompt(&mut self, (completed, candidates): (String, Vec<String>)) {
+        let bottom_row = self.content_area().height;
+        let Some((command_bar, _)) = self.command_bar.as_mut() else {
+            return;
+        };
+
+        command_bar.replace_before_caret(&completed);
+
+        if candidates.len() > 1 {
+            let anchor = Anchor::Caret(Position {
+                row: bottom_row,
+                col: command_bar.caret_position_col(),
+            });
+            let dropdown = Dropdown::new(candidates.clone(), anchor);
+            self.open_overlay(Box::new(dropdown), OverlayPurpose::CompletePath(candidates));
+        }
+    }
+
+    // Describes the grapheme under the caret codepoint by codepoint, and how it is drawn
+    fn show_character_info(&mut self) {
+        let Some((grapheme, offset, rendering)) = self.view.grapheme_under_caret() else {
+            self.message_bar.update_message("No character under the caret".to_string());
+            return;
+        };
+
+        // invisible and replaced graphemes are spelled out with escapes
+        let shown = if grapheme.trim().is_empty() || rendering.shown_as.is_some() {
+            grapheme.escape_debug().to_string()
+        } else {
+            grapheme.clone()
+        };
+        let codepoints = match grapheme.chars().count() {
+            1 => "1 codepoint".to_string(),
+            count => format!("{count} codepoints"),
+        };
+        let columns = match rendering.columns {
+            1 => "1 column".to_string(),
+            columns => format!("{columns} columns"),
+        };
+        let shown_as = rendering.shown_as.map_or_else(String::new, |shown_as| format!(", shown as {shown_as}"));
+        let mut lines = vec![format!(
+            "\"{shown}\" at byte {offset}: {codepoints}, {} bytes, {columns} {}{shown_as}",
+            grapheme.len(),
+            rendering.kind,
+        )];
+        lines.extend(grapheme.chars().map(unicode::describe));
+
+        let tooltip = Tooltip::new(lines, Anchor::Caret(self.view.caret_position()));
+        self.open_overlay(Box::new(tooltip), OverlayPurpose::Inform);
+    }
+
+    // Inserts the character with exactly that name, or lets the user pick one of those whose names match
+    fn show_character_picker(&mut self, name: &str) {
+        if let Some(c) = unicode::character(name) {
+            self.insert_character(c);
+            return;
+        }
+
+        let (characters, entries): (Vec<char>, Vec<String>) = unicode::named_characters().iter().cloned().unzip();
+        let picker = Picker::new("Insert character", entries, None).with_query(name);
+        self.open_overlay(Box::new(picker), OverlayPurpose::InsertCharacter(characters));
+    }
+
+    // Inserts the character as if it was typed, so the caret moves past combining marks and auto-wrap applies
+    fn insert_character(&mut self, c: char) {
+        let command = if c == '\n' { EditCommand::InsertNewline } else { EditCommand::Insert(c) };
+        self.view.handle_edit_command(command);
+        self.clear_mark();
+    }
+
+    fn dismiss_prompt(&mut self) -> Option<(CommandBar, PromptPurpose)> {
+        if let Some(vim) = self.vim.as_mut() {
+            vim.leave_command_mode();
+        }
         self.message_bar.set_needs_redraw(true);
+        self.command_bar.take()
     }
 
-    fn show_prompt(&mut self) {
+    fn show_prompt(&mut self, prompt: &str, purpose: PromptPurpose, value: &str) {
         let mut command_bar = CommandBar::default();
-        command_bar.set_prompt("Save as: ");
+        command_bar.set_prompt(prompt);
+        command_bar.set_value(value);
+        command_bar.set_history(self.prompt_history.entries(purpose.history_kind()));
         command_bar.resize(Size {
             height: 1,
             width: self.terminal_size.width,
         });
         command_bar.set_needs_redraw(true);
-        self.command_bar = Some(command_bar);
+        self.command_bar = Some((command_bar, purpose));
+    }
+
+    fn submit_prompt(&mut self, purpose: PromptPurpose, value: String) {
+        self.prompt_history.add(purpose.history_kind(), &value);
+
+        match purpose {
+            PromptPurpose::SaveAs => self.handle_save_as(value),
+            PromptPurpose::Open => {
+                if Path::new(&value).is_dir() {
+                    self.show_explorer(Path::new(&value));
+                } else {
+                    self.open_file(value);
+                }
+            }
+            PromptPurpose::CreateFile(directory) => self.create_file(&directory, &value),
+            PromptPurpose::RenameFile(path) => self.rename_file(&path, &value),
+            PromptPurpose::ExCommand => match ExCommand::try_from(value.as_str()) {
+                Ok(command) => self.run_ex_command(command),
+                Err(err) => self.message_bar.update_message(format!("ERR: {err}")),
+            },
+            PromptPurpose::GotoLine => match excommand::parse_position(&value) {
+                Ok((address, column)) => self.jump_to(address, column),
+                Err(err) => self.message_bar.update_message(format!("ERR: {err}")),
+            },
+            PromptPurpose::InsertCharacter => match unicode::parse_query(&value) {
+                Ok(CharacterQuery::Character(c)) => self.insert_character(c),
+                Ok(CharacterQuery::Name(name)) => self.show_character_picker(&name),
+                Err(err) => self.message_bar.update_message(format!("ERR: {err}")),
+            },
+        }
+    }
+
+    // Moves the caret to the line, and the 1-based column if given, showing it in the middle of the view
+    fn jump_to(&mut self, address: Address, column: Option<usize>) {
+        let current_row = self.view.location().row;
+        match address.resolve(current_row, self.view.line_count()) {
+            Ok(row) => {
+                let grapheme_index = column.map_or(0, |column| column.saturating_sub(1));
+                self.view.set_location(Location { grapheme_index, row });
+                self.view.center_caret();
+            }
+            Err(err) => self.message_bar.update_message(format!("ERR: {err}")),
+        }
+    }
+
+    fn run_ex_command(&mut self, command: ExCommand) {
+        match command {
+            ExCommand::Write { file: Some(file_name), force: true } => self.save(Some(&file_name)),
+            ExCommand::Write { file: Some(file_name), force: false } => self.handle_save_as(file_name),
+            ExCommand::Write { file: None, .. } => self.handle_save(),
+            ExCommand::Quit { force } => {
+                if force || !self.view.get_status().is_modified {
+                    self.remember_location();
+                    self.should_quit = true;
+                } else {
+                    self.message_bar
+                        .update_message("ERR: No write since last change (add ! to override)".to_string());
+                }
+            }
+            ExCommand::WriteQuit => {
+                if self.view.directory_root().is_some() || !self.view.is_file_loaded() {
+                    self.message_bar.update_message("ERR: No file name".to_string());
+                    return;
+                }
+                self.save(None);
+                if !self.view.get_status().is_modified {
+                    self.should_quit = true;
+                }
+            }
+            ExCommand::Edit { path: None, force: false } => self.reload(),
+            ExCommand::Edit { path: None, force: true } => match self.view.file_path().map(Path::to_path_buf) {
+                Some(path) => self.load(&path.to_string_lossy()),
+                None => self.message_bar.update_message("ERR: No file name".to_string()),
+            },
+            ExCommand::Edit { path: Some(path), force } => {
+                if Path::new(&path).is_dir() {
+                    self.show_explorer(Path::new(&path));
+                } else if force {
+                    self.load(&path);
+                } else {
+                    self.open_file(path);
+                }
+            }
+            ExCommand::Set(options) => {
+                for option in options {
+                    if let Err(err) = self.set_option(&option) {
+                        self.message_bar.update_message(format!("ERR: {err}"));
+                        return;
+                    }
+                }
+            }
+            ExCommand::Goto(address, column) => self.jump_to(address, column),
+            ExCommand::Substitute { range, pattern, replacement, global } => {
+                let Some(rows) = self.resolve_range(&range) else {
+                    return;
+                };
+                let message = match self.view.substitute(rows, &pattern, &replacement, global) {
+                    0 => format!("ERR: Pattern not found: {pattern}"),
+                    1 => "1 substitution".to_string(),
+                    count => format!("{count} substitutions"),
+                };
+                self.message_bar.update_message(message);
+            }
+            ExCommand::SaveMacro(name) => match self.macros.save(&name) {
+                Ok(()) => self.message_bar.update_message(format!("Saved the macro as {name}. Play it with :play {name}")),
+                Err(err) => self.message_bar.update_message(format!("ERR: {err}")),
+            },
+            ExCommand::PlayMacro { name, repeat } => self.play_macro(name.as_deref(), repeat),
+            ExCommand::Retab { range, to_tabs } => {
+                if let Some(rows) = self.resolve_range(&range) {
+                    let message = match self.view.retab(rows, to_tabs) {
+                        1 => "Reindented 1 line".to_string(),
+                        count => format!("Reindented {count} lines"),
+                    };
+                    self.message_bar.update_message(message);
+                }
+            }
+            ExCommand::TrimTrailing { range, only_modified } => {
+                if let Some(rows) = self.resolve_range(&range) {
+                    let message = match self.view.trim_trailing_whitespace(rows, only_modified) {
+                        1 => "Trimmed 1 line".to_string(),
+                        count => format!("Trimmed {count} lines"),
+                    };
+                    self.message_bar.update_message(message);
+                }
+            }
+            ExCommand::SqueezeBlankLines(range) => {
+                if let Some(rows) = self.resolve_range(&range) {
+                    let message = match self.view.squeeze_blank_lines(rows) {
+                        1 => "Removed 1 blank line".to_string(),
+                        count => format!("Removed {count} blank lines"),
+                    };
+                    self.message_bar.update_message(message);
+                }
+            }
+        }
+    }
+
+    // The rows of the range, or none after reporting why it is invalid
+    fn resolve_range(&mut self, range: &LineRange) -> Option<Range<usize>> {
+        let current_row = self.view.location().row;
+        let line_count = self.view.get_status().total_lines;
+        range
+            .resolve(current_row, line_count)
+            .map_err(|err| self.message_bar.update_message(format!("ERR: {err}")))
+            .ok()
+    }
+
+    // Applies an option given as `name=value`, `name` or `noname`, or shows its value for `name?`
+    fn set_option(&mut self, option: &str) -> Result<(), String> {
+        if let Some(name) = option.strip_suffix('?') {
+            let value = if name == "explorer" {
+                self.explorer.is_some().to_string()
+            } else {
+                self.config.get(name).ok_or_else(|| format!("Unknown option: {name}"))?
+            };
+            self.message_bar.update_message(format!("{name}={value}"));
+            return Ok(());
+        }
+
+        let (name, value) = option.split_once('=').map_or_else(
+            || match option.strip_prefix("no") {
+                Some(name) => (name, "false"),
+                None => (option, "true"),
+            },
+            |(name, value)| (name, value),
+        );
+
+        if name == "explorer" {
+            let show: bool = value.parse().map_err(|_| format!("Expected true or false: {option}"))?;
+            if !show {
+                self.explorer = None;
+                self.resize(self.terminal_size);
+            } else if self.explorer.is_none() {
+                self.toggle_explorer();
+                if let Some(explorer) = self.explorer.as_mut() {
+                    explorer.set_focused(false);
+                }
+            }
+            return Ok(());
+        }
+
+        if Config::is_startup_only(name) {
+            return Err(format!("{name} only takes effect at startup; set it in the config file or with a flag"));
+        }
+        self.config.set(name, value)?;
+        let errors = self.apply_config();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
     }
 
     fn handle_save(&mut self) {
-        if self.view.is_file_loaded() {
+        if let Some(changes) = self.view.directory_changes() {
+            self.review_directory_changes(changes);
+        } else if self.view.is_file_loaded() {
             self.save(None);
         } else {
-            self.show_prompt();
+            self.show_prompt("Save as: ", PromptPurpose::SaveAs, "");
+        }
+    }
+
+    fn handle_save_as(&mut self, file_name: String) {
+        if Path::new(&file_name).exists() {
+            let dialog = Dialog::confirm(
+                "Overwrite?",
+                vec![format!("{file_name} already exists."), "Do you want to replace it?".to_string()],
+            );
+            self.open_overlay(Box::new(dialog), OverlayPurpose::ConfirmOverwrite(file_name));
+        } else {
+            self.save(Some(&file_name));
         }
     }
 
     fn handle_quit(&mut self) {
-        if !self.view.get_status().is_modified || self.quit_times + 1 == QUIT_TIMES {
+        let quit_times = self.config.quit_times;
+        if !self.view.get_status().is_modified || self.quit_times + 1 >= quit_times {
+            self.remember_location();
             self.should_quit = true;
         } else if self.view.get_status().is_modified {
             self.message_bar.update_message(format!(
                 "WARNING! File has unsaved changes. Press Ctrl-Q {} more times to quit.",
-                QUIT_TIMES - self.quit_times - 1
+                quit_times - self.quit_times - 1
             ));
 
             self.quit_times += 1;
@@ -254,6 +1498,7 @@ impl Editor {
         };
 
         if result.is_ok() {
+            self.remember_location();
             self.message_bar.update_message("File saved successfully.".to_string());
         } else {
             self.message_bar.update_message("Error writing file!".to_string());
@@ -263,7 +1508,7 @@ impl Editor {
     fn reset_quit_times(&mut self) {
         if self.quit_times > 0 {
             self.quit_times = 0;
-            self.message_bar.update_message("".to_string());
+            self.message_bar.update_message(String::new());
         }
     }
 
@@ -275,7 +1520,7 @@ impl Editor {
         let bottom_bar_row = self.terminal_size.height.saturating_sub(1);
         let _ = Terminal::hide_caret();
 
-        if let Some(command_bar) = &mut self.command_bar {
+        if let Some((command_bar, _)) = &mut self.command_bar {
             command_bar.render(bottom_bar_row);
         } else {
             self.message_bar.render(bottom_bar_row);
@@ -287,19 +1532,55 @@ impl Editor {
         }
 
         if self.terminal_size.height > 2 {
+            let mut view_redrawn = self.view.needs_redraw();
             self.view.render(0);
+
+            if let Some(explorer) = self.explorer.as_mut() {
+                view_redrawn |= explorer.needs_redraw();
+                explorer.render(0);
+            }
+
+            // overlays are drawn on top of the view and the panels, so they need to be redrawn with them
+            if let Some((overlay, _)) = self.overlay.as_mut() {
+                if view_redrawn {
+                    overlay.set_needs_redraw(true);
+                }
+                overlay.render(0);
+            }
         }
         
-        let new_caret_pos = if let Some(command_bar) = &self.command_bar {
+        let overlay_caret_pos = self
+            .overlay
+            .as_ref()
+            .and_then(|(overlay, _)| overlay.caret_position());
+
+        let explorer_caret_pos = self
+            .explorer
+            .as_ref()
+            .filter(|explorer| explorer.is_focused())
+            .map(FileExplorer::caret_position);
+
+        let new_caret_pos = if let Some((command_bar, _)) = &self.command_bar {
             Position {
                 row: bottom_bar_row,
                 col: command_bar.caret_position_col(),
             }
+        } else if let Some(position) = overlay_caret_pos.or(explorer_caret_pos) {
+            position
         } else {
             self.view.caret_position()
         };
 
 
+        let caret_shape = match self.vim.as_ref().map(Vim::mode) {
+            None => CaretShape::Default,
+            Some(VimMode::Insert | VimMode::Command) => CaretShape::Bar,
+            Some(VimMode::Normal | VimMode::Visual) => CaretShape::Block,
+        };
+        if caret_shape != self.caret_shape && Terminal::set_caret_shape(caret_shape).is_ok() {
+            self.caret_shape = caret_shape;
+        }
+
         if let Err(e) = Terminal::move_caret_to(new_caret_pos) {
             error!("Failed to move caret: {e:?}");
         }
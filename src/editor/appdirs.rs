@@ -0,0 +1,21 @@
+use std::{env, path::PathBuf};
+
+use super::NAME;
+
+// Where the editor keeps its state between sessions, following the XDG base directory spec
+pub fn state_dir() -> Option<PathBuf> {
+    env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state")))
+        .map(|dir| dir.join(NAME))
+}
+
+// Where the user's settings live, following the XDG base directory spec
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join(NAME))
+}
@@ -0,0 +1,66 @@
+use super::{edit::EditCommand, kill::KillCommand, moves::MoveCommand, system::SystemCommand, Command};
+
+// A command the user can run by name, e.g. from the command palette
+pub struct Action {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub command: Command,
+}
+
+const fn action(name: &'static str, description: &'static str, command: Command) -> Action {
+    Action { name, description, command }
+}
+
+pub const ACTIONS: &[Action] = &[
+    action("save", "Save the current file", Command::System(SystemCommand::Save)),
+    action("save-as", "Save the current file under a new name", Command::System(SystemCommand::SaveAs)),
+    action("open-file", "Open a file by path", Command::System(SystemCommand::Open)),
+    action("find-file", "Fuzzy-find a file below the working directory", Command::System(SystemCommand::FindFile)),
+    action("recent-files", "Pick one of the recently used files", Command::System(SystemCommand::RecentFiles)),
+    action("reload-file", "Read the current file from disk again", Command::System(SystemCommand::Reload)),
+    action("toggle-explorer", "Show, focus or hide the file explorer", Command::System(SystemCommand::ToggleExplorer)),
+    action("inspect-character", "Show the codepoints, names, bytes and width of the character under the caret", Command::System(SystemCommand::InspectCharacter)),
+    action("insert-character", "Insert a character by codepoint (U+2192), digraph (->) or name", Command::System(SystemCommand::InsertCharacter)),
+    action("command-palette", "Search and run any action", Command::System(SystemCommand::CommandPalette)),
+    action("key-bindings", "List every key binding", Command::System(SystemCommand::KeyBindings)),
+    action("toggle-soft-wrap", "Wrap long lines onto several screen rows, or scroll them sideways", Command::System(SystemCommand::ToggleSoftWrap)),
+    action("toggle-whitespace", "Show spaces, tabs, line ends and control characters, or hide them", Command::System(SystemCommand::ToggleWhitespace)),
+    action("goto-line", "Jump to a line and column, an offset such as +10 or a percentage", Command::System(SystemCommand::GotoLine)),
+    action("command-line", "Type a command such as :w, :e path or :%s/a/b/g", Command::System(SystemCommand::CommandLine)),
+    action("dismiss", "Close the prompt or give the focus back to the text", Command::System(SystemCommand::Dismiss)),
+    action("universal-argument", "Repeat the next action 4 times, or as often as the digits typed next say", Command::System(SystemCommand::UniversalArgument)),
+    action("record-macro", "Start or stop recording a keyboard macro", Command::System(SystemCommand::RecordMacro)),
+    action("play-macro", "Replay the keyboard macro recorded last", Command::System(SystemCommand::PlayMacro)),
+    action("quit", "Quit the editor", Command::System(SystemCommand::Quit)),
+    action("move-up", "Move the caret up a line", Command::Move(MoveCommand::Up)),
+    action("move-down", "Move the caret down a line", Command::Move(MoveCommand::Down)),
+    action("move-left", "Move the caret left a character", Command::Move(MoveCommand::Left)),
+    action("move-right", "Move the caret right a character", Command::Move(MoveCommand::Right)),
+    action("page-up", "Move the caret up a screen", Command::Move(MoveCommand::PageUp)),
+    action("page-down", "Move the caret down a screen", Command::Move(MoveCommand::PageDown)),
+    action("line-start", "Move the caret to the start of the line", Command::Move(MoveCommand::Home)),
+    action("line-end", "Move the caret to the end of the line", Command::Move(MoveCommand::End)),
+    action("word-forward", "Move the caret to the end of the word", Command::Move(MoveCommand::WordForward)),
+    action("word-backward", "Move the caret to the start of the word", Command::Move(MoveCommand::WordBackward)),
+    action("paragraph-up", "Move the caret to the blank line before the paragraph", Command::Move(MoveCommand::ParagraphUp)),
+    action("paragraph-down", "Move the caret to the blank line after the paragraph", Command::Move(MoveCommand::ParagraphDown)),
+    action("insert-tab", "Insert a tab, or complete what was typed into a prompt", Command::Edit(EditCommand::Insert('\t'))),
+    action("insert-newline", "Split the line at the caret", Command::Edit(EditCommand::InsertNewline)),
+    action("delete-char-backward", "Delete the character before the caret", Command::Edit(EditCommand::Delete)),
+    action("delete-char-forward", "Delete the character under the caret", Command::Edit(EditCommand::DeleteBackward)),
+    action("delete-word-backward", "Delete the word before the caret", Command::Edit(EditCommand::DeleteWordBackward)),
+    action("delete-word-forward", "Delete up to the end of the word after the caret", Command::Edit(EditCommand::DeleteWordForward)),
+    action("indent", "Indent the line or selected lines by one level", Command::Edit(EditCommand::Indent)),
+    action("dedent", "Take one level of indentation from the line or selected lines", Command::Edit(EditCommand::Dedent)),
+    action("reflow-paragraph", "Fill the paragraph or selected lines up to the wrap column", Command::Edit(EditCommand::Reflow)),
+    action("set-mark", "Start selecting a region at the caret", Command::Kill(KillCommand::SetMark)),
+    action("kill-line", "Cut the rest of the line, or the line break at its end, into the kill ring", Command::Kill(KillCommand::KillLine)),
+    action("kill-region", "Cut the text between the mark and the caret into the kill ring", Command::Kill(KillCommand::KillRegion)),
+    action("copy-region", "Copy the text between the mark and the caret into the kill ring", Command::Kill(KillCommand::CopyRegion)),
+    action("yank", "Insert the text killed last", Command::Kill(KillCommand::Yank)),
+    action("yank-pop", "Replace the text just yanked with the kill before it", Command::Kill(KillCommand::YankPop)),
+];
+
+pub fn find(name: &str) -> Option<&'static Action> {
+    ACTIONS.iter().find(|action| action.name == name)
+}
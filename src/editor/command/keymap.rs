@@ -0,0 +1,593 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MediaKeyCode, ModifierKeyCode};
+
+use super::action::{self, Action};
+
+// A key together with the modifiers held while pressing it
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub const ESCAPE: Self = Self::plain(KeyCode::Esc);
+
+    // A key pressed without modifiers
+    pub const fn plain(code: KeyCode) -> Self {
+        Self { code, modifiers: KeyModifiers::NONE }
+    }
+
+    // Shift only counts for characters when other modifiers are held, otherwise it is part of the character
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if modifiers == KeyModifiers::SHIFT => Self {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: KeyModifiers::NONE,
+            },
+            KeyCode::Char(c) if modifiers != KeyModifiers::NONE => Self {
+                code: KeyCode::Char(c.to_ascii_lowercase()),
+                modifiers,
+            },
+            // terminals report Shift-Tab as a key of its own
+            KeyCode::BackTab => Self { code: KeyCode::Tab, modifiers: modifiers | KeyModifiers::SHIFT },
+            _ => Self { code, modifiers },
+        }
+    }
+
+    // The character the chord types, if it isn't a shortcut
+    pub fn text(self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c) if self.modifiers == KeyModifiers::NONE => Some(c),
+            _ => None,
+        }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = text.get(..prefix.len())?;
+    head.eq_ignore_ascii_case(prefix).then(|| &text[prefix.len()..])
+}
+
+// The modifiers as they are written before a key, in the order they are written in
+const MODIFIER_NAMES: [(&str, KeyModifiers); 6] = [
+    ("Ctrl-", KeyModifiers::CONTROL),
+    ("Alt-", KeyModifiers::ALT),
+    ("Shift-", KeyModifiers::SHIFT),
+    ("Super-", KeyModifiers::SUPER),
+    ("Hyper-", KeyModifiers::HYPER),
+    ("Meta-", KeyModifiers::META),
+];
+
+// The names of the keys that don't type a character, apart from the function keys. Parsing and printing chords
+// both go through this list, so every key prints as a name that parses back to it.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("Enter", KeyCode::Enter),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Tab", KeyCode::Tab),
+    ("BackTab", KeyCode::BackTab),
+    ("Esc", KeyCode::Esc),
+    ("Space", KeyCode::Char(' ')),
+    ("Null", KeyCode::Null),
+    ("CapsLock", KeyCode::CapsLock),
+    ("ScrollLock", KeyCode::ScrollLock),
+    ("NumLock", KeyCode::NumLock),
+    ("PrintScreen", KeyCode::PrintScreen),
+    ("Pause", KeyCode::Pause),
+    ("Menu", KeyCode::Menu),
+    ("KeypadBegin", KeyCode::KeypadBegin),
+    ("MediaPlay", KeyCode::Media(MediaKeyCode::Play)),
+    ("MediaPause", KeyCode::Media(MediaKeyCode::Pause)),
+    ("MediaPlayPause", KeyCode::Media(MediaKeyCode::PlayPause)),
+    ("MediaReverse", KeyCode::Media(MediaKeyCode::Reverse)),
+    ("MediaStop", KeyCode::Media(MediaKeyCode::Stop)),
+    ("MediaFastForward", KeyCode::Media(MediaKeyCode::FastForward)),
+    ("MediaRewind", KeyCode::Media(MediaKeyCode::Rewind)),
+    ("MediaTrackNext", KeyCode::Media(MediaKeyCode::TrackNext)),
+    ("MediaTrackPrevious", KeyCode::Media(MediaKeyCode::TrackPrevious)),
+    ("MediaRecord", KeyCode::Media(MediaKeyCode::Record)),
+    ("MediaLowerVolume", KeyCode::Media(MediaKeyCode::LowerVolume)),
+    ("MediaRaiseVolume", KeyCode::Media(MediaKeyCode::RaiseVolume)),
+    ("MediaMuteVolume", KeyCode::Media(MediaKeyCode::MuteVolume)),
+    ("LeftShift", KeyCode::Modifier(ModifierKeyCode::LeftShift)),
+    ("LeftControl", KeyCode::Modifier(ModifierKeyCode::LeftControl)),
+    ("LeftAlt", KeyCode::Modifier(ModifierKeyCode::LeftAlt)),
+    ("LeftSuper", KeyCode::Modifier(ModifierKeyCode::LeftSuper)),
+    ("LeftHyper", KeyCode::Modifier(ModifierKeyCode::LeftHyper)),
+    ("LeftMeta", KeyCode::Modifier(ModifierKeyCode::LeftMeta)),
+    ("RightShift", KeyCode::Modifier(ModifierKeyCode::RightShift)),
+    ("RightControl", KeyCode::Modifier(ModifierKeyCode::RightControl)),
+    ("RightAlt", KeyCode::Modifier(ModifierKeyCode::RightAlt)),
+    ("RightSuper", KeyCode::Modifier(ModifierKeyCode::RightSuper)),
+    ("RightHyper", KeyCode::Modifier(ModifierKeyCode::RightHyper)),
+    ("RightMeta", KeyCode::Modifier(ModifierKeyCode::RightMeta)),
+    ("IsoLevel3Shift", KeyCode::Modifier(ModifierKeyCode::IsoLevel3Shift)),
+    ("IsoLevel5Shift", KeyCode::Modifier(ModifierKeyCode::IsoLevel5Shift)),
+];
+
+// Characters that can't be written as themselves in a key sequence, which is split at whitespace
+fn needs_code_point(c: char) -> bool {
+    c.is_whitespace() || c.is_control()
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    // Parses chords such as `Ctrl-K`, `Alt-Shift-Left`, `F1`, `Space`, `a` or `U+0009`
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        const SHORT_MODIFIER_NAMES: [(&str, KeyModifiers); 3] = [
+            ("C-", KeyModifiers::CONTROL),
+            ("M-", KeyModifiers::ALT),
+            ("S-", KeyModifiers::SHIFT),
+        ];
+
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = text;
+        'modifiers: while key.len() > 1 {
+            for (prefix, modifier) in MODIFIER_NAMES.into_iter().chain(SHORT_MODIFIER_NAMES) {
+                if let Some(rest) = strip_prefix_ignore_case(key, prefix).filter(|rest| !rest.is_empty()) {
+                    modifiers |= modifier;
+                    key = rest;
+                    continue 'modifiers;
+                }
+            }
+            break;
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "del" => KeyCode::Delete,
+                "escape" => KeyCode::Esc,
+                name => KEY_NAMES
+                    .iter()
+                    .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+                    .map(|&(_, code)| code)
+                    .or_else(|| name.strip_prefix('f').and_then(|number| number.parse().ok()).map(KeyCode::F))
+                    .or_else(|| {
+                        name.strip_prefix("u+")
+                            .and_then(|code| u32::from_str_radix(code, 16).ok())
+                            .and_then(char::from_u32)
+                            .map(KeyCode::Char)
+                    })
+                    .ok_or_else(|| format!("Unknown key: {text}"))?,
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (name, modifier) in MODIFIER_NAMES {
+            if self.modifiers.contains(modifier) {
+                write!(formatter, "{name}")?;
+            }
+        }
+
+        if let Some((name, _)) = KEY_NAMES.iter().find(|(_, code)| *code == self.code) {
+            return write!(formatter, "{name}");
+        }
+        match self.code {
+            KeyCode::Char(c) if needs_code_point(c) => write!(formatter, "U+{:04X}", u32::from(c)),
+            KeyCode::Char(c) if self.modifiers == KeyModifiers::NONE => write!(formatter, "{c}"),
+            KeyCode::Char(c) => write!(formatter, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(number) => write!(formatter, "F{number}"),
+            other => write!(formatter, "{other:?}"),
+        }
+    }
+}
+
+fn parse_sequence(keys: &str) -> Result<Vec<KeyChord>, String> {
+    let sequence = keys
+        .split_whitespace()
+        .map(KeyChord::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    if sequence.is_empty() {
+        return Err("Empty key sequence".to_string());
+    }
+    Ok(sequence)
+}
+
+pub fn describe_sequence(sequence: &[KeyChord]) -> String {
+    sequence.iter().map(ToString::to_string).collect::<Vec<_>>().join(" ")
+}
+
+// Which part of the editor has the focus, each of which can bind keys differently
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyMode {
+    Editor,
+    Prompt,
+    Explorer,
+    Overlay,
+}
+
+impl KeyMode {
+    const ALL: [Self; 4] = [Self::Editor, Self::Prompt, Self::Explorer, Self::Overlay];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Editor => "editor",
+            Self::Prompt => "prompt",
+            Self::Explorer => "explorer",
+            Self::Overlay => "overlay",
+        }
+    }
+}
+
+// The bindings in effect before the configuration changes any
+const DEFAULT_BINDINGS: &[(&str, &str)] = &[
+    ("Up", "move-up"),
+    ("Down", "move-down"),
+    ("Left", "move-left"),
+    ("Right", "move-right"),
+    ("PageUp", "page-up"),
+    ("PageDown", "page-down"),
+    ("Home", "line-start"),
+    ("End", "line-end"),
+    ("Tab", "insert-tab"),
+    ("Shift-Tab", "dedent"),
+    ("Enter", "insert-newline"),
+    ("Backspace", "delete-char-backward"),
+    ("Delete", "delete-char-forward"),
+    ("Ctrl-Left", "word-backward"),
+    ("Ctrl-Right", "word-forward"),
+    ("Ctrl-Up", "paragraph-up"),
+    ("Ctrl-Down", "paragraph-down"),
+    ("Ctrl-W", "delete-word-backward"),
+    ("Ctrl-Backspace", "delete-word-backward"),
+    ("Alt-Backspace", "delete-word-backward"),
+    ("Ctrl-Delete", "delete-word-forward"),
+    ("Ctrl-Q", "quit"),
+    ("Ctrl-S", "save"),
+    ("Ctrl-P", "find-file"),
+    ("Ctrl-B", "toggle-explorer"),
+    ("Ctrl-O", "open-file"),
+    ("Ctrl-R", "recent-files"),
+    ("Ctrl-E", "command-line"),
+    ("Ctrl-G", "goto-line"),
+    ("Alt-Z", "toggle-soft-wrap"),
+    ("Alt-H", "toggle-whitespace"),
+    ("Alt-Q", "reflow-paragraph"),
+    ("Alt-I", "inspect-character"),
+    ("Ctrl-K", "insert-character"),
+    ("Ctrl-Shift-P", "command-palette"),
+    ("F1", "command-palette"),
+    ("F3", "record-macro"),
+    ("F4", "play-macro"),
+    ("Esc", "dismiss"),
+];
+
+// Bindings on top of the default ones for the emacs editing mode
+pub const EMACS_BINDINGS: &[(&str, &str)] = &[
+    ("C-f", "move-right"),
+    ("C-b", "move-left"),
+    ("C-n", "move-down"),
+    ("C-p", "move-up"),
+    ("M-f", "word-forward"),
+    ("M-b", "word-backward"),
+    ("M-d", "delete-word-forward"),
+    ("C-a", "line-start"),
+    ("C-e", "line-end"),
+    ("C-v", "page-down"),
+    ("M-v", "page-up"),
+    ("C-d", "delete-char-forward"),
+    ("C-k", "kill-line"),
+    ("C-w", "kill-region"),
+    ("M-w", "copy-region"),
+    ("C-y", "yank"),
+    ("M-y", "yank-pop"),
+    ("C-Space", "set-mark"),
+    ("C-u", "universal-argument"),
+    ("C-g", "dismiss"),
+    ("M-x", "command-palette"),
+    ("M-g g", "goto-line"),
+    ("M-g M-g", "goto-line"),
+    ("C-x C-s", "save"),
+    ("C-x C-w", "save-as"),
+    ("C-x C-f", "open-file"),
+    ("C-x C-r", "recent-files"),
+    ("C-x d", "toggle-explorer"),
+    ("C-x (", "record-macro"),
+    ("C-x )", "record-macro"),
+    ("C-x e", "play-macro"),
+    ("C-x =", "inspect-character"),
+    ("C-x 8 Enter", "insert-character"),
+    ("C-x C-c", "quit"),
+];
+
+// `None` unbinds a key sequence that is bound for every mode
+type Bindings = HashMap<Vec<KeyChord>, Option<&'static Action>>;
+
+pub enum KeyLookup {
+    Action(&'static Action),
+    // the keys so far start a longer sequence
+    Pending,
+    Unbound,
+}
+
+// Maps key sequences to actions, for every mode and for single modes
+pub struct Keymap {
+    global: Bindings,
+    modes: HashMap<KeyMode, Bindings>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl Keymap {
+    // The default bindings, with those of a profile such as `EMACS_BINDINGS` replacing them where they overlap
+    pub fn new(profile: &[(&str, &str)]) -> Self {
+        let mut keymap = Self {
+            global: HashMap::new(),
+            modes: HashMap::new(),
+        };
+        for (keys, action) in DEFAULT_BINDINGS.iter().chain(profile) {
+            let bound = keymap.bind(None, keys, action);
+            debug_assert!(bound.is_ok(), "Invalid default binding {keys}: {bound:?}");
+        }
+        keymap
+    }
+
+    // Binds the key sequence, e.g. `Ctrl-K Ctrl-S`, to the named action, or unbinds it for the action `none`.
+    // Without a mode the binding applies to every mode that doesn't bind the sequence itself.
+    pub fn bind(&mut self, mode: Option<&str>, keys: &str, action: &str) -> Result<(), String> {
+        let sequence = parse_sequence(keys)?;
+        let action = match action {
+            "none" => None,
+            name => Some(action::find(name).ok_or_else(|| format!("Unknown action: {name}"))?),
+        };
+
+        let bindings = match mode {
+            None => &mut self.global,
+            Some(name) => {
+                let mode = KeyMode::ALL
+                    .into_iter()
+                    .find(|mode| mode.name() == name)
+                    .ok_or_else(|| format!("Unknown mode: {name}"))?;
+                self.modes.entry(mode).or_default()
+            }
+        };
+
+        if mode.is_none() && action.is_none() {
+            bindings.remove(&sequence);
+        } else {
+            bindings.insert(sequence, action);
+        }
+        Ok(())
+    }
+
+    pub fn lookup(&self, mode: KeyMode, keys: &[KeyChord]) -> KeyLookup {
+        let mode_bindings = self.modes.get(&mode);
+        let exact = mode_bindings
+            .and_then(|bindings| bindings.get(keys))
+            .or_else(|| self.global.get(keys));
+
+        match exact {
+            Some(Some(action)) => KeyLookup::Action(action),
+            Some(None) => KeyLookup::Unbound,
+            None => {
+                let is_prefix = mode_bindings
+                    .into_iter()
+                    .chain([&self.global])
+                    .flat_map(HashMap::iter)
+                    .any(|(sequence, action)| {
+                        action.is_some() && sequence.len() > keys.len() && sequence.starts_with(keys)
+                    });
+                if is_prefix {
+                    KeyLookup::Pending
+                } else {
+                    KeyLookup::Unbound
+                }
+            }
+        }
+    }
+
+    // The key sequences that trigger the action while editing text
+    pub fn bindings_for(&self, action: &Action) -> Vec<String> {
+        let mut bindings: Vec<String> = self
+            .global
+            .iter()
+            .filter(|(sequence, _)| {
+                matches!(self.lookup(KeyMode::Editor, sequence), KeyLookup::Action(bound) if bound.name == action.name)
+            })
+            .map(|(sequence, _)| describe_sequence(sequence))
+            .collect();
+        bindings.sort();
+        bindings
+    }
+
+    // Every binding as its mode, keys and action, sorted by mode and keys
+    pub fn all_bindings(&self) -> Vec<(&'static str, String, &'static Action)> {
+        let global = self.global.iter().map(|binding| ("all", binding));
+        let modes = self
+            .modes
+            .iter()
+            .flat_map(|(mode, bindings)| bindings.iter().map(|binding| (mode.name(), binding)));
+
+        let mut bindings: Vec<_> = global
+            .chain(modes)
+            .filter_map(|(mode, (sequence, action))| action.map(|action| (mode, describe_sequence(sequence), action)))
+            .collect();
+        bindings.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+        bindings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_prints_as_a_chord_that_parses_back() {
+        let mut codes: Vec<KeyCode> = KEY_NAMES.iter().map(|&(_, code)| code).collect();
+        codes.extend((0..=u8::MAX).map(KeyCode::F));
+        codes.extend(['a', 'Z', '-', '(', 'é', ' ', '\t', '\u{a0}', '\0', '\u{85}'].map(KeyCode::Char));
+
+        let modifiers = [
+            KeyModifiers::NONE,
+            KeyModifiers::SHIFT,
+            KeyModifiers::CONTROL,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+            KeyModifiers::ALT | KeyModifiers::SUPER | KeyModifiers::HYPER | KeyModifiers::META,
+        ];
+        for code in codes {
+            for modifiers in modifiers {
+                let chord = KeyChord::new(code, modifiers);
+                let text = chord.to_string();
+                assert!(!text.contains(char::is_whitespace), "{text:?}");
+                assert!(text.parse::<KeyChord>() == Ok(chord), "{code:?} with {modifiers:?} printed as {text:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn every_key_code_has_a_name() {
+        let named = |code| KEY_NAMES.iter().any(|&(_, named)| named == code);
+        // crossterm's key codes, apart from characters and function keys
+        for code in [
+            KeyCode::Backspace,
+            KeyCode::Enter,
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Home,
+            KeyCode::End,
+            KeyCode::PageUp,
+            KeyCode::PageDown,
+            KeyCode::Tab,
+            KeyCode::BackTab,
+            KeyCode::Delete,
+            KeyCode::Insert,
+            KeyCode::Null,
+            KeyCode::Esc,
+            KeyCode::CapsLock,
+            KeyCode::ScrollLock,
+            KeyCode::NumLock,
+            KeyCode::PrintScreen,
+            KeyCode::Pause,
+            KeyCode::Menu,
+            KeyCode::KeypadBegin,
+        ] {
+            assert!(named(code), "{code:?}");
+        }
+        assert_eq!(KEY_NAMES.iter().filter(|(_, code)| matches!(code, KeyCode::Media(_))).count(), 13);
+        assert_eq!(KEY_NAMES.iter().filter(|(_, code)| matches!(code, KeyCode::Modifier(_))).count(), 14);
+    }
+
+    fn chord(text: &str) -> KeyChord {
+        text.parse().unwrap_or_else(|err| panic!("{text:?}: {err}"))
+    }
+
+    fn sequence(keys: &str) -> Vec<KeyChord> {
+        parse_sequence(keys).unwrap_or_else(|err| panic!("{keys:?}: {err}"))
+    }
+
+    // The action the keys trigger, `...` while they start a longer sequence, or `-` when they do nothing
+    fn lookup(keymap: &Keymap, mode: KeyMode, keys: &str) -> &'static str {
+        match keymap.lookup(mode, &sequence(keys)) {
+            KeyLookup::Action(action) => action.name,
+            KeyLookup::Pending => "...",
+            KeyLookup::Unbound => "-",
+        }
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        let ctrl_k = KeyChord::new(KeyCode::Char('k'), KeyModifiers::CONTROL);
+        assert!(chord("Ctrl-K") == ctrl_k);
+        assert!(chord("ctrl-k") == ctrl_k);
+        assert!(chord("C-k") == ctrl_k);
+        assert!(chord("Alt-Shift-Left") == KeyChord::new(KeyCode::Left, KeyModifiers::ALT | KeyModifiers::SHIFT));
+        assert!(chord("M-S-Left") == chord("Alt-Shift-Left"));
+        let super_hyper_meta = KeyModifiers::SUPER | KeyModifiers::HYPER | KeyModifiers::META;
+        assert!(chord("Super-Hyper-Meta-x") == KeyChord::new(KeyCode::Char('x'), super_hyper_meta));
+        // Shift on its own is part of the character
+        assert!(chord("Shift-a") == chord("A"));
+        assert!(chord("A").text() == Some('A'));
+        let ctrl_shift = KeyModifiers::CONTROL | KeyModifiers::SHIFT;
+        assert!(chord("Ctrl-Shift-P") == KeyChord::new(KeyCode::Char('p'), ctrl_shift));
+        // a dash after a modifier is the key itself
+        assert!(chord("Ctrl--") == KeyChord::new(KeyCode::Char('-'), KeyModifiers::CONTROL));
+        assert!(chord("-").text() == Some('-'));
+    }
+
+    #[test]
+    fn parses_named_keys() {
+        assert!(chord("Space") == KeyChord::plain(KeyCode::Char(' ')));
+        assert!(chord("esc") == KeyChord::ESCAPE);
+        assert!(chord("Escape") == KeyChord::ESCAPE);
+        assert!(chord("Del") == chord("Delete"));
+        assert!(chord("PageDown") == KeyChord::plain(KeyCode::PageDown));
+        assert!(chord("F12") == KeyChord::plain(KeyCode::F(12)));
+        assert!(chord("F13") == KeyChord::plain(KeyCode::F(13)));
+        assert!(chord("BackTab") == chord("Shift-Tab"));
+        assert!(chord("U+0009") == KeyChord::plain(KeyCode::Char('\t')));
+
+        assert_eq!(KeyChord::from_str("Ctrl-Foo").err(), Some("Unknown key: Ctrl-Foo".to_string()));
+        assert!(KeyChord::from_str("F256").is_err());
+        assert!(KeyChord::from_str("").is_err());
+        assert_eq!(parse_sequence("  ").err(), Some("Empty key sequence".to_string()));
+        assert_eq!(describe_sequence(&sequence("C-x   C-s")), "Ctrl-X Ctrl-S");
+    }
+
+    #[test]
+    fn looks_up_sequences_and_their_prefixes() {
+        let keymap = Keymap::new(EMACS_BINDINGS);
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "Ctrl-S"), "save");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "C-x"), "...");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "C-x C-s"), "save");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "C-x C-z"), "-");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "M-g"), "...");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "M-g g"), "goto-line");
+        // the emacs bindings replace the default ones where they overlap
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "C-n"), "move-down");
+        assert_eq!(lookup(&Keymap::default(), KeyMode::Editor, "C-x"), "-");
+    }
+
+    #[test]
+    fn falls_back_from_modes_to_every_mode() {
+        let mut keymap = Keymap::default();
+        keymap.bind(Some("prompt"), "Ctrl-G", "dismiss").unwrap();
+        keymap.bind(Some("explorer"), "Ctrl-S", "none").unwrap();
+        keymap.bind(Some("overlay"), "Ctrl-J Ctrl-J", "quit").unwrap();
+        keymap.bind(None, "Ctrl-Q", "none").unwrap();
+
+        assert_eq!(lookup(&keymap, KeyMode::Prompt, "Ctrl-G"), "dismiss");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "Ctrl-G"), "goto-line");
+        // unbinding for a mode hides the binding for every mode
+        assert_eq!(lookup(&keymap, KeyMode::Explorer, "Ctrl-S"), "-");
+        assert_eq!(lookup(&keymap, KeyMode::Prompt, "Ctrl-S"), "save");
+        // sequences of a mode only start in that mode
+        assert_eq!(lookup(&keymap, KeyMode::Overlay, "Ctrl-J"), "...");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "Ctrl-J"), "-");
+        assert_eq!(lookup(&keymap, KeyMode::Overlay, "Ctrl-J Ctrl-J"), "quit");
+        assert_eq!(lookup(&keymap, KeyMode::Editor, "Ctrl-Q"), "-");
+
+        assert_eq!(keymap.bind(Some("menu"), "Ctrl-G", "dismiss").err(), Some("Unknown mode: menu".to_string()));
+        assert_eq!(keymap.bind(None, "Ctrl-G", "fly").err(), Some("Unknown action: fly".to_string()));
+        assert_eq!(keymap.bindings_for(action::find("save").unwrap()), ["Ctrl-S"]);
+    }
+}
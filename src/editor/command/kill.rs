@@ -0,0 +1,10 @@
+// Commands working on the mark and the kill ring, as in emacs
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum KillCommand {
+    SetMark,
+    KillLine,
+    KillRegion,
+    CopyRegion,
+    Yank,
+    YankPop,
+}
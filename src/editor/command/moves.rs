@@ -1,6 +1,4 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum MoveCommand {
     Up,
     Down,
@@ -10,28 +8,8 @@ pub enum MoveCommand {
     PageDown,
     Home,
     End,
+    WordForward,
+    WordBackward,
+    ParagraphUp,
+    ParagraphDown,
 }
-
-impl TryFrom<KeyEvent> for MoveCommand {
-    type Error = String;
-
-    fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
-        let KeyEvent {code, modifiers, ..} = event;
-
-        if modifiers == KeyModifiers::NONE {
-            return match code {
-                KeyCode::Left => Ok(MoveCommand::Left),
-                KeyCode::Right => Ok(MoveCommand::Right),
-                KeyCode::Up => Ok(MoveCommand::Up),
-                KeyCode::Down => Ok(MoveCommand::Down),
-                KeyCode::Home => Ok(MoveCommand::Home),
-                KeyCode::End => Ok(MoveCommand::End),
-                KeyCode::PageUp => Ok(MoveCommand::PageUp),
-                KeyCode::PageDown => Ok(MoveCommand::PageDown),
-                _ => Err(format!("Unsupported code: {code:?}")),
-            }
-        } else {
-            return Err(format!("Unsupported key code {code:?} or modifier {modifiers:?}"));
-        }
-    }
-}
\ No newline at end of file
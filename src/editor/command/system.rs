@@ -1,32 +1,26 @@
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-
 use crate::editor::size::Size;
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum SystemCommand {
     Save,
+    SaveAs,
+    Reload,
+    CommandPalette,
+    CommandLine,
+    KeyBindings,
+    InspectCharacter,
+    InsertCharacter,
+    FindFile,
+    ToggleExplorer,
+    Open,
+    GotoLine,
+    ToggleSoftWrap,
+    ToggleWhitespace,
+    RecentFiles,
+    UniversalArgument,
+    RecordMacro,
+    PlayMacro,
     Resize(Size),
     Quit,
     Dismiss,
 }
-
-impl TryFrom<KeyEvent> for SystemCommand {
-    type Error = String;
-
-    fn try_from(event: KeyEvent) -> Result<Self, Self::Error> {
-        let KeyEvent {code, modifiers, ..} = event;
- 
-        if modifiers == KeyModifiers::CONTROL {
-            match code {
-                KeyCode::Char('q') => Ok(Self::Quit),
-                KeyCode::Char('s') => Ok(Self::Save),
-                _ => Err(format!("Unsupported CONTROL+{code:?} combination")),
-            }
-        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::Esc) {
-            Ok(Self::Dismiss)
-        } else {
-            Err(format!(
-                "Unsupported key code {code:?} or modifier {modifiers:?}"
-            ))
-        }
-    }
-}
\ No newline at end of file
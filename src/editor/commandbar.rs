@@ -1,9 +1,9 @@
-use std::{cmp::min, io::Error};
+use std::io::Error;
 
 use super::{
-    command::edit::EditCommand, 
+    command::{edit::EditCommand, moves::MoveCommand},
     uicomponent::UiComponent, 
-    view::line::Line, 
+    view::line::{DisplayStyle, Line},
     Size, 
     Terminal
 };
@@ -12,6 +12,11 @@ use super::{
 pub struct CommandBar {
     prompt: String,
     value: Line,
+    caret: usize,
+    scroll_offset: usize,
+    history: Vec<String>,
+    history_index: Option<usize>,
+    draft: String,
     needs_redraw: bool,
     size: Size,
 }
@@ -19,25 +24,134 @@ pub struct CommandBar {
 impl CommandBar {
     pub fn handle_edit_command(&mut self, command: EditCommand) {
         match command {
-            EditCommand::Insert(character) => self.value.append_char(character),
-            EditCommand::Delete | EditCommand::InsertNewline=> {}
-            EditCommand::DeleteBackward => self.value.delete_last(),
+            EditCommand::Insert(character) => {
+                let old_len = self.value.grapheme_count();
+                self.value.insert_char(character, self.caret);
+                if self.value.grapheme_count() > old_len {
+                    self.caret = self.caret.saturating_add(1);
+                }
+            }
+            EditCommand::InsertNewline | EditCommand::Reflow | EditCommand::Indent | EditCommand::Dedent => {}
+            EditCommand::Delete => {
+                if self.caret > 0 {
+                    self.caret -= 1;
+                    self.value.delete_grapheme_at(self.caret);
+                }
+            }
+            EditCommand::DeleteBackward => self.value.delete_grapheme_at(self.caret),
+            EditCommand::DeleteWordForward => {
+                let end = self.value.next_word_end(self.caret);
+                self.value.delete_range(self.caret..end);
+            }
+            EditCommand::DeleteWordBackward => {
+                let start = self.value.previous_word_start(self.caret);
+                self.value.delete_range(start..self.caret);
+                self.caret = start;
+            }
         }
+        self.scroll_caret_into_view();
         self.set_needs_redraw(true);
     }
 
+    pub fn handle_move_command(&mut self, command: MoveCommand) {
+        match command {
+            MoveCommand::Left => self.caret = self.caret.saturating_sub(1),
+            MoveCommand::Right => self.caret = self.caret.saturating_add(1).min(self.value.grapheme_count()),
+            MoveCommand::Home => self.caret = 0,
+            MoveCommand::End => self.caret = self.value.grapheme_count(),
+            MoveCommand::Up => self.previous_history_entry(),
+            MoveCommand::Down => self.next_history_entry(),
+            MoveCommand::WordForward => self.caret = self.value.next_word_end(self.caret),
+            MoveCommand::WordBackward => self.caret = self.value.previous_word_start(self.caret),
+            MoveCommand::PageUp | MoveCommand::PageDown | MoveCommand::ParagraphUp | MoveCommand::ParagraphDown => {}
+        }
+        self.scroll_caret_into_view();
+        self.set_needs_redraw(true);
+    }
+
+    // Earlier entries of this kind of prompt, oldest first, for browsing with Up and Down
+    pub fn set_history(&mut self, history: Vec<String>) {
+        self.history = history;
+        self.history_index = None;
+    }
+
+    fn previous_history_entry(&mut self) {
+        let index = match self.history_index {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.value();
+                self.history.len() - 1
+            }
+            Some(index) => index.saturating_sub(1),
+        };
+
+        self.history_index = Some(index);
+        self.set_value(&self.history[index].clone());
+    }
+
+    fn next_history_entry(&mut self) {
+        let Some(index) = self.history_index else {
+            return;
+        };
+
+        if index + 1 < self.history.len() {
+            self.history_index = Some(index + 1);
+            self.set_value(&self.history[index + 1].clone());
+        } else {
+            // moving past the newest entry brings back what was typed before browsing
+            self.history_index = None;
+            self.set_value(&self.draft.clone());
+        }
+    }
+
+    // How many columns are left for the value after the prompt
+    fn value_area_width(&self) -> usize {
+        self.size.width.saturating_sub(self.prompt.len())
+    }
+
+    fn scroll_caret_into_view(&mut self) {
+        let caret_col = self.value.width_until(self.caret, DisplayStyle::default());
+        let width = self.value_area_width();
+
+        if caret_col < self.scroll_offset {
+            self.scroll_offset = caret_col;
+        } else if caret_col >= self.scroll_offset.saturating_add(width) {
+            self.scroll_offset = caret_col.saturating_add(1).saturating_sub(width);
+        }
+    }
+
     pub fn caret_position_col(&self) -> usize {
-        let max_width = self
-            .prompt
-            .len()
-            .saturating_add(self.value.grapheme_count());
-        min(max_width, self.size.width)
+        let caret_col = self
+            .value
+            .width_until(self.caret, DisplayStyle::default())
+            .saturating_sub(self.scroll_offset);
+        self.prompt.len().saturating_add(caret_col).min(self.size.width)
     }
 
     pub fn value(&self) -> String {
         self.value.to_string()
     }
 
+    pub fn value_before_caret(&self) -> String {
+        self.value.get_graphemes(0..self.caret)
+    }
+
+    pub fn set_value(&mut self, value: &str) {
+        self.value = Line::from(value);
+        self.caret = self.value.grapheme_count();
+        self.scroll_caret_into_view();
+        self.set_needs_redraw(true);
+    }
+
+    // Replaces everything left of the caret, keeping the caret right after the new text
+    pub fn replace_before_caret(&mut self, text: &str) {
+        let after = self.value.get_graphemes(self.caret..self.value.grapheme_count());
+        self.value = Line::from(format!("{text}{after}").as_str());
+        self.caret = Line::from(text).grapheme_count();
+        self.scroll_caret_into_view();
+        self.set_needs_redraw(true);
+    }
+
     pub fn set_prompt(&mut self, prompt: &str) {
         self.prompt = prompt.to_string();
     }
@@ -54,22 +168,22 @@ impl UiComponent for CommandBar {
 
     fn set_size(&mut self, size: Size) {
         self.size = size;
+        self.scroll_caret_into_view();
     }
 
     fn draw(&mut self, origin: usize) -> Result<(), Error> {
-        let area_for_value = self.size.width.saturating_sub(self.prompt.len()); //this is how much space there is between the right side of the prompt and the edge of the bar
-        let value_end = self.value.width(); // we always want to show the left part of the value, therefore the end of the visible range we try to access will be equal to the full width
-        let value_start = value_end.saturating_sub(area_for_value); //This should give us the start for the grapheme subrange we want to print out.
+        let value_start = self.scroll_offset;
+        let value_end = value_start.saturating_add(self.value_area_width());
         let message = format!(
             "{}{}",
             self.prompt,
-            self.value.get(value_start..value_end)
+            self.value.get(value_start..value_end, DisplayStyle::default())
         );
-        let to_print = if message.len() <= self.size.width {
+        let to_print = if self.prompt.len() <= self.size.width {
             message
         } else {
             String::new()
         };
         Terminal::print_row(origin, &to_print)
     }
-}
\ No newline at end of file
+}
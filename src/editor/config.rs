@@ -0,0 +1,358 @@
+use std::{fs, path::PathBuf, str::FromStr, time::Duration};
+
+use log::LevelFilter;
+use toml::{Table, Value};
+
+use super::{appdirs, theme::Theme, view::{IndentPolicy, LineNumbers, TrimOnSave}};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+// How keys edit the text
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum EditingMode {
+    Default,
+    // vim-style modal editing
+    Vim,
+    // emacs key bindings, with a kill ring and prefix keys
+    Emacs,
+}
+
+impl EditingMode {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Vim => "vim",
+            Self::Emacs => "emacs",
+        }
+    }
+}
+
+impl FromStr for EditingMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "default" => Ok(Self::Default),
+            "vim" => Ok(Self::Vim),
+            "emacs" => Ok(Self::Emacs),
+            _ => Err(format!("expected default, vim or emacs, got {value:?}")),
+        }
+    }
+}
+
+// A key sequence bound to an action in the `[keys]` table, or in a `[keys.<mode>]` table
+pub struct KeyBinding {
+    pub mode: Option<String>,
+    pub keys: String,
+    pub action: String,
+}
+
+// Settings read from the config file, which command-line flags and `:set` can override
+#[allow(clippy::struct_excessive_bools)]
+pub struct Config {
+    pub tab_width: usize,
+    // shows tabs with a glyph instead of blank space
+    pub show_tabs: bool,
+    // shows spaces, tabs, line ends and control characters
+    pub show_whitespace: bool,
+    pub indent_style: IndentPolicy,
+    pub trim_on_save: TrimOnSave,
+    pub soft_wrap: bool,
+    pub line_numbers: LineNumbers,
+    // the column reflowing fills paragraphs up to, and auto-wrap breaks lines at
+    pub wrap_column: usize,
+    pub auto_wrap: bool,
+    pub theme: Theme,
+    pub message_timeout: Duration,
+    pub quit_times: u8,
+    pub editing_mode: EditingMode,
+    pub log_level: LevelFilter,
+    pub log_file: PathBuf,
+    pub key_bindings: Vec<KeyBinding>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            show_tabs: false,
+            show_whitespace: false,
+            indent_style: IndentPolicy::Auto,
+            trim_on_save: TrimOnSave::Off,
+            soft_wrap: false,
+            line_numbers: LineNumbers::Off,
+            wrap_column: 80,
+            auto_wrap: false,
+            theme: Theme::Default,
+            message_timeout: Duration::from_secs(5),
+            quit_times: 3,
+            editing_mode: EditingMode::Default,
+            log_level: LevelFilter::Debug,
+            log_file: PathBuf::from("editor.log"),
+            key_bindings: Vec::new(),
+        }
+    }
+}
+
+// `tab_width`, `tab-width` and `tabwidth` all name the same setting
+fn normalize_key(key: &str) -> String {
+    key.chars().filter(|c| *c != '_' && *c != '-').collect::<String>().to_lowercase()
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "on" | "yes" => Ok(true),
+        "false" | "off" | "no" => Ok(false),
+        _ => Err(format!("expected true or false, got {value:?}")),
+    }
+}
+
+fn parse_number<T: FromStr + PartialOrd + Copy>(value: &str, min: T) -> Result<T, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|number| *number >= min)
+        .ok_or_else(|| format!("expected a positive number, got {value:?}"))
+}
+
+impl Config {
+    // Reads the config file, falling back to the defaults for anything it doesn't set.
+    // A missing file is fine; broken settings are reported, and the defaults kept in their place.
+    pub fn load() -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let Some(path) = Self::path() else {
+            return (config, Vec::new());
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return (config, Vec::new());
+        };
+
+        let errors = config.apply_toml(&content);
+        let errors = errors.into_iter().map(|err| format!("{}: {err}", path.display())).collect();
+        (config, errors)
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        appdirs::config_dir().map(|dir| dir.join(CONFIG_FILE_NAME))
+    }
+
+    // Applies every valid setting, returning what was wrong with the others
+    fn apply_toml(&mut self, content: &str) -> Vec<String> {
+        let table = match content.parse::<Table>() {
+            Ok(table) => table,
+            Err(err) => return vec![err.message().to_string()],
+        };
+
+        let mut errors = Vec::new();
+        for (key, value) in table {
+            if key == "keys" {
+                self.add_key_bindings(None, value, &mut errors);
+                continue;
+            }
+
+            let value = match value {
+                Value::String(text) => text,
+                other => other.to_string(),
+            };
+            if let Err(err) = self.set(&key, &value) {
+                errors.push(err);
+            }
+        }
+        errors
+    }
+
+    fn add_key_bindings(&mut self, mode: Option<&str>, value: Value, errors: &mut Vec<String>) {
+        let Value::Table(table) = value else {
+            errors.push("keys: expected a table of key sequences and actions".to_string());
+            return;
+        };
+
+        for (keys, value) in table {
+            match value {
+                Value::String(action) => self.key_bindings.push(KeyBinding {
+                    mode: mode.map(str::to_string),
+                    keys,
+                    action,
+                }),
+                table @ Value::Table(_) if mode.is_none() => self.add_key_bindings(Some(&keys), table, errors),
+                _ => errors.push(format!("keys: expected an action name for {keys:?}")),
+            }
+        }
+    }
+
+    // Applies flags such as `--tab-width=8`, `--soft-wrap` or `--no-soft-wrap`.
+    // Returns the arguments that aren't flags, and what was wrong with the invalid flags.
+    pub fn apply_args(&mut self, args: impl IntoIterator<Item = String>) -> (Vec<String>, Vec<String>) {
+        let mut rest = Vec::new();
+        let mut errors = Vec::new();
+
+        for arg in args {
+            let Some(flag) = arg.strip_prefix("--") else {
+                rest.push(arg);
+                continue;
+            };
+
+            let (key, value) = match flag.split_once('=') {
+                Some((key, value)) => (key, value),
+                None => flag.strip_prefix("no-").map_or((flag, "true"), |key| (key, "false")),
+            };
+            if let Err(err) = self.set(key, value) {
+                errors.push(format!("--{flag}: {err}"));
+            }
+        }
+        (rest, errors)
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let result = match normalize_key(key).as_str() {
+            "tabwidth" => parse_number(value, 1).map(|width| self.tab_width = width),
+            "showtabs" => parse_bool(value).map(|show| self.show_tabs = show),
+            "showwhitespace" => parse_bool(value).map(|show| self.show_whitespace = show),
+            "indentstyle" => value.parse().map(|style| self.indent_style = style),
+            "trimonsave" => value.parse().map(|trim| self.trim_on_save = trim),
+            "softwrap" => parse_bool(value).map(|wrap| self.soft_wrap = wrap),
+            "linenumbers" => value.parse().map(|numbers| self.line_numbers = numbers),
+            "wrapcolumn" => parse_number(value, 1).map(|column| self.wrap_column = column),
+            "autowrap" => parse_bool(value).map(|wrap| self.auto_wrap = wrap),
+            "theme" => value.parse().map(|theme| self.theme = theme),
+            "messagetimeout" => parse_number(value, 1).map(|secs| self.message_timeout = Duration::from_secs(secs)),
+            "quittimes" => parse_number(value, 1).map(|times| self.quit_times = times),
+            "editingmode" => value.parse().map(|mode| self.editing_mode = mode),
+            "loglevel" => LevelFilter::from_str(value)
+                .map(|level| self.log_level = level)
+                .map_err(|_| format!("expected off, error, warn, info, debug or trace, got {value:?}")),
+            "logfile" => {
+                self.log_file = PathBuf::from(value);
+                Ok(())
+            }
+            _ => return Err(format!("Unknown option: {key}")),
+        };
+        result.map_err(|err| format!("{key}: {err}"))
+    }
+
+    // Whether the setting is only read at startup, as the logger can't be set up again once it runs
+    pub fn is_startup_only(key: &str) -> bool {
+        matches!(normalize_key(key).as_str(), "loglevel" | "logfile")
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        let value = match normalize_key(key).as_str() {
+            "tabwidth" => self.tab_width.to_string(),
+            "showtabs" => self.show_tabs.to_string(),
+            "showwhitespace" => self.show_whitespace.to_string(),
+            "indentstyle" => self.indent_style.name().to_string(),
+            "trimonsave" => self.trim_on_save.name().to_string(),
+            "softwrap" => self.soft_wrap.to_string(),
+            "linenumbers" => self.line_numbers.name().to_string(),
+            "wrapcolumn" => self.wrap_column.to_string(),
+            "autowrap" => self.auto_wrap.to_string(),
+            "theme" => self.theme.name().to_string(),
+            "messagetimeout" => self.message_timeout.as_secs().to_string(),
+            "quittimes" => self.quit_times.to_string(),
+            "editingmode" => self.editing_mode.name().to_string(),
+            "loglevel" => self.log_level.to_string().to_lowercase(),
+            "logfile" => self.log_file.display().to_string(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn apply_toml_reads_every_valid_setting() {
+        let mut config = Config::default();
+        let errors = config.apply_toml("tab_width = 8\nsoft_wrap = true\ntheme = \"blue\"\nlog_file = \"x.log\"");
+        assert!(errors.is_empty());
+        assert_eq!(config.tab_width, 8);
+        assert!(config.soft_wrap);
+        assert!(config.theme == Theme::Blue);
+        assert_eq!(config.log_file, PathBuf::from("x.log"));
+    }
+
+    #[test]
+    fn apply_toml_reports_all_broken_settings_and_applies_the_rest() {
+        let mut config = Config::default();
+        let errors = config.apply_toml("tab_width = \"wide\"\ncolour = 3\nsoft_wrap = 1\nwrap_column = 72");
+        assert_eq!(
+            errors,
+            [
+                "Unknown option: colour".to_string(),
+                "soft_wrap: expected true or false, got \"1\"".to_string(),
+                "tab_width: expected a positive number, got \"wide\"".to_string(),
+            ]
+        );
+        assert_eq!(config.tab_width, 4);
+        assert!(!config.soft_wrap);
+        assert_eq!(config.wrap_column, 72);
+    }
+
+    #[test]
+    fn apply_toml_reports_broken_files_and_key_tables() {
+        let mut config = Config::default();
+        assert_eq!(config.apply_toml("tab_width = ").len(), 1);
+        assert_eq!(config.apply_toml("keys = 3"), ["keys: expected a table of key sequences and actions"]);
+        assert_eq!(config.apply_toml("[keys]\nctrl-s = 1"), ["keys: expected an action name for \"ctrl-s\""]);
+    }
+
+    #[test]
+    fn apply_toml_collects_key_bindings_by_mode() {
+        let mut config = Config::default();
+        let errors = config.apply_toml("[keys]\nctrl-s = \"save\"\n[keys.normal]\nx = \"quit\"");
+        assert!(errors.is_empty());
+        let bindings: Vec<(Option<&str>, &str, &str)> = config
+            .key_bindings
+            .iter()
+            .map(|binding| (binding.mode.as_deref(), binding.keys.as_str(), binding.action.as_str()))
+            .collect();
+        assert_eq!(bindings, [(None, "ctrl-s", "save"), (Some("normal"), "x", "quit")]);
+    }
+
+    #[test]
+    fn flags_override_the_config_file() {
+        let mut config = Config::default();
+        assert!(config.apply_toml("tab_width = 2\nsoft_wrap = true").is_empty());
+        let (rest, errors) = config.apply_args(args(&["--tab-width=8", "--no-soft-wrap", "file.txt"]));
+        assert_eq!(rest, ["file.txt"]);
+        assert!(errors.is_empty());
+        assert_eq!(config.tab_width, 8);
+        assert!(!config.soft_wrap);
+    }
+
+    #[test]
+    fn apply_args_reports_every_bad_flag() {
+        let mut config = Config::default();
+        let (rest, errors) = config.apply_args(args(&["--tab-width=0", "--auto-wrap", "--bogus", "a", "b"]));
+        assert_eq!(rest, ["a", "b"]);
+        assert_eq!(
+            errors,
+            ["--tab-width=0: tab-width: expected a positive number, got \"0\"", "--bogus: Unknown option: bogus"]
+        );
+        assert!(config.auto_wrap);
+    }
+
+    #[test]
+    fn set_accepts_any_spelling_of_a_key() {
+        let mut config = Config::default();
+        assert_eq!(config.set("Tab-Width", " 3 "), Ok(()));
+        assert_eq!(config.get("tabwidth").as_deref(), Some("3"));
+        assert_eq!(config.set("theme", "neon"), Err("theme: expected default or blue, got \"neon\"".to_string()));
+        assert_eq!(config.get("theme").as_deref(), Some("default"));
+        assert!(config.get("nonsense").is_none());
+    }
+
+    #[test]
+    fn log_settings_are_startup_only() {
+        assert!(Config::is_startup_only("log-level"));
+        assert!(Config::is_startup_only("log_file"));
+        assert!(!Config::is_startup_only("tab_width"));
+    }
+}
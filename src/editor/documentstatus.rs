@@ -4,6 +4,8 @@ pub struct DocumentStatus {
     pub curr_line_ind: usize,
     pub is_modified: bool,
     pub file_name: String,
+    // the editing mode, when editing is modal
+    pub mode: Option<String>,
 }
 
 impl DocumentStatus {
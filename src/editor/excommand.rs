@@ -0,0 +1,454 @@
+use std::{ops::Range, path::Path, str::FromStr};
+
+use regex::Regex;
+
+use super::{files, macros::Repeat};
+
+// A command typed into the ":" command line
+pub enum ExCommand {
+    Write { file: Option<String>, force: bool },
+    Quit { force: bool },
+    WriteQuit,
+    Edit { path: Option<String>, force: bool },
+    Set(Vec<String>),
+    // a line, and optionally a 1-based column
+    Goto(Address, Option<usize>),
+    // the replacement refers to groups of the pattern as `$1` or `${name}`, and writes `$` as `$$`
+    Substitute { range: LineRange, pattern: Regex, replacement: String, global: bool },
+    SaveMacro(String),
+    PlayMacro { name: Option<String>, repeat: Repeat },
+    // indentation with tabs or spaces, as the indentation style says unless given
+    Retab { range: LineRange, to_tabs: Option<bool> },
+    TrimTrailing { range: LineRange, only_modified: bool },
+    SqueezeBlankLines(LineRange),
+}
+
+struct CommandName {
+    name: &'static str,
+    // the shortest abbreviation that is accepted
+    min_len: usize,
+    takes_path: bool,
+    takes_range: bool,
+}
+
+const COMMAND_NAMES: &[CommandName] = &[
+    CommandName { name: "write", min_len: 1, takes_path: true, takes_range: false },
+    CommandName { name: "quit", min_len: 1, takes_path: false, takes_range: false },
+    CommandName { name: "wq", min_len: 2, takes_path: false, takes_range: false },
+    CommandName { name: "xit", min_len: 1, takes_path: false, takes_range: false },
+    CommandName { name: "edit", min_len: 1, takes_path: true, takes_range: false },
+    CommandName { name: "set", min_len: 2, takes_path: false, takes_range: false },
+    CommandName { name: "goto", min_len: 2, takes_path: false, takes_range: false },
+    CommandName { name: "substitute", min_len: 1, takes_path: false, takes_range: true },
+    CommandName { name: "macro", min_len: 3, takes_path: false, takes_range: false },
+    CommandName { name: "play", min_len: 2, takes_path: false, takes_range: false },
+    CommandName { name: "retab", min_len: 3, takes_path: false, takes_range: true },
+    CommandName { name: "trim", min_len: 2, takes_path: false, takes_range: true },
+    CommandName { name: "squeeze", min_len: 2, takes_path: false, takes_range: true },
+];
+
+#[derive(Clone, Copy)]
+pub enum Address {
+    Line(usize),
+    Current,
+    Last,
+    Offset(isize),
+    // a percentage of the way through the file
+    Percent(usize),
+}
+
+pub enum LineRange {
+    CurrentLine,
+    All,
+    Lines(Address, Address),
+}
+
+impl Address {
+    // Resolves the address to a row index, given the current row and the number of lines
+    pub fn resolve(self, current: usize, line_count: usize) -> Result<usize, String> {
+        let row = match self {
+            Self::Line(0) => 0,
+            Self::Line(line) => line - 1,
+            Self::Current => current,
+            Self::Last => line_count.saturating_sub(1),
+            Self::Offset(offset) => current
+                .checked_add_signed(offset)
+                .ok_or_else(|| "Invalid range".to_string())?,
+            Self::Percent(percent) if percent <= 100 => (line_count * percent).div_ceil(100).saturating_sub(1),
+            Self::Percent(_) => return Err("Invalid percentage".to_string()),
+        };
+
+        if row >= line_count.max(1) {
+            return Err("Invalid range".to_string());
+        }
+        Ok(row)
+    }
+}
+
+impl LineRange {
+    pub fn resolve(&self, current: usize, line_count: usize) -> Result<Range<usize>, String> {
+        match self {
+            Self::CurrentLine => Ok(current..current.saturating_add(1)),
+            Self::All => Ok(0..line_count),
+            Self::Lines(start, end) => {
+                let start = start.resolve(current, line_count)?;
+                let end = end.resolve(current, line_count)?;
+                if start > end {
+                    return Err("Backwards range given".to_string());
+                }
+                Ok(start..end.saturating_add(1))
+            }
+        }
+    }
+}
+
+// Splits off a leading address such as `12`, `.`, `$`, `+3` or `50%`
+fn parse_address(input: &str) -> Result<(Option<Address>, &str), String> {
+    fn number<T: FromStr>(digits: &str) -> Result<T, String> {
+        digits.parse().map_err(|_| format!("Not a line number: {digits}"))
+    }
+    let digits = |s: &str| s.len() - s.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+    if let Some(rest) = input.strip_prefix('.') {
+        Ok((Some(Address::Current), rest))
+    } else if let Some(rest) = input.strip_prefix('$') {
+        Ok((Some(Address::Last), rest))
+    } else if let Some(sign @ ('+' | '-')) = input.chars().next() {
+        // a sign on its own moves by one line
+        let len = digits(&input[1..]);
+        let amount: isize = if len == 0 { 1 } else { number(&input[1..=len])? };
+        let offset = if sign == '-' { -amount } else { amount };
+        Ok((Some(Address::Offset(offset)), &input[1 + len..]))
+    } else {
+        let len = digits(input);
+        if len == 0 {
+            return Ok((None, input));
+        }
+        let number = number(&input[..len])?;
+        match input[len..].strip_prefix('%') {
+            Some(rest) => Ok((Some(Address::Percent(number)), rest)),
+            None => Ok((Some(Address::Line(number)), &input[len..])),
+        }
+    }
+}
+
+fn parse_range(input: &str) -> Result<(Option<LineRange>, &str), String> {
+    if let Some(rest) = input.strip_prefix('%') {
+        return Ok((Some(LineRange::All), rest));
+    }
+
+    let (Some(start), rest) = parse_address(input)? else {
+        return Ok((None, input));
+    };
+    if let Some(rest) = rest.strip_prefix(',') {
+        if let (Some(end), rest) = parse_address(rest)? {
+            return Ok((Some(LineRange::Lines(start, end)), rest));
+        }
+    }
+    Ok((Some(LineRange::Lines(start, start)), rest))
+}
+
+// Parses a position to jump to, such as `120`, `120:8`, `+10`, `-5`, `$` or `50%`
+pub fn parse_position(input: &str) -> Result<(Address, Option<usize>), String> {
+    let input = input.trim();
+    let (Some(address), rest) = parse_address(input)? else {
+        return Err(format!("Not a line number: {input}"));
+    };
+
+    match rest.strip_prefix([':', ',']) {
+        None if rest.is_empty() => Ok((address, None)),
+        Some(column) => column
+            .parse()
+            .map(|column| (address, Some(column)))
+            .map_err(|_| format!("Not a column number: {column}")),
+        None => Err(format!("Not a line number: {input}")),
+    }
+}
+
+fn lookup_name(name: &str) -> Option<&'static CommandName> {
+    COMMAND_NAMES
+        .iter()
+        .find(|command| name.len() >= command.min_len && command.name.starts_with(name))
+}
+
+// Parses `s/pattern/replacement/flags`, where any punctuation can take the place of `/` and the pattern is a
+// regular expression
+fn parse_substitute(range: LineRange, args: &str) -> Result<ExCommand, String> {
+    let mut chars = args.chars();
+    let Some(delimiter) = chars.next().filter(|c| !c.is_alphanumeric() && !c.is_whitespace()) else {
+        return Err("Expected s/pattern/replacement/".to_string());
+    };
+
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut escaped = false;
+    for c in chars {
+        if escaped {
+            // an escaped delimiter stands for itself, any other escape is kept as typed
+            if c != delimiter {
+                part.push('\\');
+            }
+            part.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter && parts.len() < 2 {
+            parts.push(std::mem::take(&mut part));
+        } else {
+            part.push(c);
+        }
+    }
+    parts.push(part);
+
+    let mut parts = parts.into_iter();
+    let pattern = parts.next().unwrap_or_default();
+    let replacement = parts.next().unwrap_or_default();
+    let flags = parts.next().unwrap_or_default();
+
+    if pattern.is_empty() {
+        return Err("Empty search pattern".to_string());
+    }
+    if let Some(flag) = flags.chars().find(|flag| *flag != 'g') {
+        return Err(format!("Unknown flag: {flag}"));
+    }
+    let pattern = Regex::new(&pattern).map_err(|err| format!("Invalid pattern: {err}"))?;
+
+    Ok(ExCommand::Substitute {
+        range,
+        pattern,
+        replacement,
+        global: flags.contains('g'),
+    })
+}
+
+// Parses `[name] [count|all]`, where a missing count means once
+fn parse_play(args: &str) -> Result<ExCommand, String> {
+    let mut words = args.split_whitespace();
+    let (mut name, mut count) = (words.next(), words.next());
+    if count.is_none() && name.is_some_and(|word| word == "all" || word.parse::<usize>().is_ok()) {
+        count = name.take();
+    }
+    if let Some(extra) = words.next() {
+        return Err(format!("Trailing characters: {extra}"));
+    }
+
+    let repeat = match count {
+        None => Repeat::Times(1),
+        Some("all") => Repeat::ToEnd,
+        Some(count) => Repeat::Times(count.parse().map_err(|_| format!("Not a count: {count}"))?),
+    };
+    Ok(ExCommand::PlayMacro { name: name.map(str::to_string), repeat })
+}
+
+impl TryFrom<&str> for ExCommand {
+    type Error = String;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        let input = input.trim().trim_start_matches(':');
+        let (range, rest) = parse_range(input)?;
+
+        let name_len = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_alphabetic()).len();
+        let (name, rest) = rest.split_at(name_len);
+        let (force, args) = rest
+            .strip_prefix('!')
+            .map_or((false, rest.trim()), |args| (true, args.trim()));
+        let path = (!args.is_empty()).then(|| args.to_string());
+
+        if name.is_empty() {
+            return match range {
+                Some(LineRange::Lines(address, _)) if args.is_empty() && !force => Ok(Self::Goto(address, None)),
+                _ if input.is_empty() => Err("No command given".to_string()),
+                _ => Err(format!("Not an editor command: {input}")),
+            };
+        }
+
+        let Some(command) = lookup_name(name) else {
+            return Err(format!("Not an editor command: {name}"));
+        };
+        if range.is_some() && !command.takes_range {
+            return Err(format!("{} does not take a range", command.name));
+        }
+
+        match command.name {
+            "write" => Ok(Self::Write { file: path, force }),
+            "quit" => Ok(Self::Quit { force }),
+            "wq" | "xit" => Ok(Self::WriteQuit),
+            "edit" => Ok(Self::Edit { path, force }),
+            "set" if args.is_empty() => Err("Expected an option, e.g. :set name=value".to_string()),
+            "set" => Ok(Self::Set(args.split_whitespace().map(str::to_string).collect())),
+            "goto" => parse_position(args).map(|(address, column)| Self::Goto(address, column)),
+            "macro" if args.is_empty() || args.contains(char::is_whitespace) => {
+                Err("Expected a name for the macro, e.g. :macro name".to_string())
+            }
+            "macro" => Ok(Self::SaveMacro(args.to_string())),
+            "play" => parse_play(args),
+            "retab" => {
+                let to_tabs = match args {
+                    "" => None,
+                    "tabs" => Some(true),
+                    "spaces" => Some(false),
+                    _ => return Err(format!("Expected tabs or spaces, got {args}")),
+                };
+                Ok(Self::Retab { range: range.unwrap_or(LineRange::All), to_tabs })
+            }
+            "trim" => match args {
+                "" | "modified" => Ok(Self::TrimTrailing {
+                    range: range.unwrap_or(LineRange::All),
+                    only_modified: !args.is_empty(),
+                }),
+                _ => Err(format!("Trailing characters: {args}")),
+            },
+            "squeeze" if args.is_empty() => Ok(Self::SqueezeBlankLines(range.unwrap_or(LineRange::All))),
+            "squeeze" => Err(format!("Trailing characters: {args}")),
+            _ => parse_substitute(range.unwrap_or(LineRange::CurrentLine), rest),
+        }
+    }
+}
+
+// Completes the command name, or the path argument of commands that take one, as far as it is unambiguous.
+// Returns the completed input and every input the completed part could still become.
+pub fn complete(partial: &str) -> (String, Vec<String>) {
+    let rest = parse_range(partial).map_or(partial, |(_, rest)| rest);
+    let (range, rest) = partial.split_at(partial.len() - rest.len());
+
+    if let Some((name, arg)) = rest.split_once(' ') {
+        let takes_path = lookup_name(name.trim_end_matches('!')).is_some_and(|command| command.takes_path);
+        if !takes_path {
+            return (partial.to_string(), Vec::new());
+        }
+
+        let prefix = format!("{range}{name} ");
+        let (completed, candidates) = files::complete_path(arg.trim_start(), Path::new(""));
+        let candidates = candidates.into_iter().map(|path| format!("{prefix}{path}")).collect();
+        return (format!("{prefix}{completed}"), candidates);
+    }
+
+    let names: Vec<&str> = COMMAND_NAMES
+        .iter()
+        .map(|command| command.name)
+        .filter(|name| name.starts_with(rest))
+        .collect();
+    let common = files::common_prefix(&names);
+
+    let completed = if common.len() > rest.len() {
+        format!("{range}{common}")
+    } else {
+        partial.to_string()
+    };
+    let candidates = names.into_iter().map(|name| format!("{range}{name}")).collect();
+    (completed, candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(input: &str, current: usize, line_count: usize) -> Result<Range<usize>, String> {
+        let (range, rest) = parse_range(input)?;
+        assert!(rest.is_empty(), "{input:?} left {rest:?}");
+        range.expect("a range").resolve(current, line_count)
+    }
+
+    fn substitute(input: &str) -> (Range<usize>, String, String, bool) {
+        match ExCommand::try_from(input) {
+            Ok(ExCommand::Substitute { range, pattern, replacement, global }) => {
+                (range.resolve(4, 10).unwrap(), pattern.to_string(), replacement, global)
+            }
+            Ok(_) => panic!("{input:?} is not a substitution"),
+            Err(err) => panic!("{input:?}: {err}"),
+        }
+    }
+
+    #[test]
+    fn resolves_addresses_to_rows() {
+        assert_eq!(Address::Line(1).resolve(4, 10), Ok(0));
+        assert_eq!(Address::Line(0).resolve(4, 10), Ok(0));
+        assert_eq!(Address::Current.resolve(4, 10), Ok(4));
+        assert_eq!(Address::Last.resolve(4, 10), Ok(9));
+        assert_eq!(Address::Offset(3).resolve(4, 10), Ok(7));
+        assert_eq!(Address::Offset(-4).resolve(4, 10), Ok(0));
+        assert_eq!(Address::Percent(50).resolve(4, 10), Ok(4));
+        assert_eq!(Address::Percent(100).resolve(4, 10), Ok(9));
+        // the empty buffer still has the line the caret is on
+        assert_eq!(Address::Last.resolve(0, 0), Ok(0));
+
+        assert!(Address::Line(11).resolve(4, 10).is_err());
+        assert!(Address::Offset(-5).resolve(4, 10).is_err());
+        assert!(Address::Offset(6).resolve(4, 10).is_err());
+        assert!(Address::Percent(101).resolve(4, 10).is_err());
+    }
+
+    #[test]
+    fn resolves_ranges_to_rows() {
+        assert_eq!(rows("%", 4, 10), Ok(0..10));
+        assert_eq!(rows(".", 4, 10), Ok(4..5));
+        assert_eq!(rows("$", 4, 10), Ok(9..10));
+        assert_eq!(rows("3,$", 4, 10), Ok(2..10));
+        assert_eq!(rows(".,+2", 4, 10), Ok(4..7));
+        assert_eq!(rows("-,+", 4, 10), Ok(3..6));
+        assert_eq!(rows("1,50%", 4, 10), Ok(0..5));
+        assert_eq!(LineRange::CurrentLine.resolve(4, 10), Ok(4..5));
+
+        assert_eq!(rows("5,2", 4, 10), Err("Backwards range given".to_string()));
+        assert_eq!(rows("1,11", 4, 10), Err("Invalid range".to_string()));
+        assert_eq!(rows("-9", 4, 10), Err("Invalid range".to_string()));
+    }
+
+    #[test]
+    fn parses_positions() {
+        let position = |input| parse_position(input).map(|(address, column)| (address.resolve(4, 10), column));
+        assert_eq!(position("7"), Ok((Ok(6), None)));
+        assert_eq!(position(" 7:12 "), Ok((Ok(6), Some(12))));
+        assert_eq!(position("7,3"), Ok((Ok(6), Some(3))));
+        assert_eq!(position("+2"), Ok((Ok(6), None)));
+        assert_eq!(position("-"), Ok((Ok(3), None)));
+        assert_eq!(position("$"), Ok((Ok(9), None)));
+        assert_eq!(position("20%"), Ok((Ok(1), None)));
+
+        assert_eq!(position("abc"), Err("Not a line number: abc".to_string()));
+        assert_eq!(position("7x"), Err("Not a line number: 7x".to_string()));
+        assert_eq!(position("7:x"), Err("Not a column number: x".to_string()));
+        assert!(position("99999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn parses_substitutions() {
+        assert_eq!(substitute("s/a/b/"), (4..5, "a".to_string(), "b".to_string(), false));
+        assert_eq!(substitute(":%s/a/b/g"), (0..10, "a".to_string(), "b".to_string(), true));
+        assert_eq!(substitute("2,$s/a/b"), (1..10, "a".to_string(), "b".to_string(), false));
+        assert_eq!(substitute(".,+1s/a/"), (4..6, "a".to_string(), String::new(), false));
+        // an escaped delimiter is part of the pattern, other escapes are passed on to the regular expression
+        assert_eq!(substitute(r"s/a\/b/c\/d/"), (4..5, "a/b".to_string(), "c/d".to_string(), false));
+        assert_eq!(substitute(r"s/\d+/#/g"), (4..5, r"\d+".to_string(), "#".to_string(), true));
+        assert_eq!(substitute("s#/usr#/opt#"), (4..5, "/usr".to_string(), "/opt".to_string(), false));
+        // the last part is all flags, even if it holds the delimiter
+        assert!(ExCommand::try_from("s/a/b/c/").is_err_and(|err| err == "Unknown flag: c"));
+    }
+
+    #[test]
+    fn rejects_bad_substitutions() {
+        let error = |input| ExCommand::try_from(input).err().unwrap_or_else(|| panic!("{input:?} parsed"));
+        assert_eq!(error("s"), "Expected s/pattern/replacement/");
+        assert_eq!(error("s a b"), "Expected s/pattern/replacement/");
+        assert_eq!(error("s//b/"), "Empty search pattern");
+        assert_eq!(error("s/a/b/x"), "Unknown flag: x");
+        assert!(error("s/(/b/").starts_with("Invalid pattern:"));
+        assert_eq!(error("3,xs/a/b/"), "Not an editor command: 3,xs/a/b/");
+        assert_eq!(error("%w"), "write does not take a range");
+    }
+
+    #[test]
+    fn parses_ranges_without_a_command_as_jumps() {
+        let line = |input| match ExCommand::try_from(input) {
+            Ok(ExCommand::Goto(address, None)) => address.resolve(4, 10),
+            _ => Err(format!("{input:?} is not a jump")),
+        };
+        assert_eq!(line(":12"), Err("Invalid range".to_string()));
+        assert_eq!(line(":8"), Ok(7));
+        assert_eq!(line("$"), Ok(9));
+        assert_eq!(line("+3"), Ok(7));
+        assert_eq!(line("."), Ok(4));
+        assert!(ExCommand::try_from("%").is_err());
+        assert!(ExCommand::try_from("").is_err());
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::Error,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    command::{edit::EditCommand, moves::MoveCommand, system::SystemCommand, Command},
+    overlay::fit_to_width,
+    position::Position,
+    size::Size,
+    terminal::Terminal,
+    theme::Theme,
+    uicomponent::UiComponent,
+};
+
+pub const EXPLORER_WIDTH: usize = 30;
+
+// What the editor should do in response to a command given to the explorer
+pub enum ExplorerAction {
+    None,
+    Open(PathBuf),
+    Create(PathBuf),
+    Rename(PathBuf),
+    Delete(PathBuf),
+    EditDirectory(PathBuf),
+    Unfocus,
+}
+
+struct Entry {
+    path: PathBuf,
+    depth: usize,
+    is_dir: bool,
+}
+
+#[derive(Default)]
+pub struct FileExplorer {
+    root: PathBuf,
+    entries: Vec<Entry>,
+    expanded: HashSet<PathBuf>,
+    show_hidden: bool,
+    selected: usize,
+    scroll: usize,
+    focused: bool,
+    needs_redraw: bool,
+    size: Size,
+    theme: Theme,
+}
+
+impl FileExplorer {
+    pub fn new(root: &Path) -> Self {
+        let mut explorer = Self::default();
+        explorer.set_root(root);
+        explorer
+    }
+
+    pub fn set_root(&mut self, root: &Path) {
+        self.root = fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+        self.selected = 0;
+        self.scroll = 0;
+        self.refresh();
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.set_needs_redraw(true);
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.set_needs_redraw(true);
+    }
+
+    pub const fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    // Re-reads the tree from disk, keeping expanded directories expanded
+    pub fn refresh(&mut self) {
+        let mut entries = Vec::new();
+        self.collect_entries(&self.root.clone(), 0, &mut entries);
+        self.entries = entries;
+        self.select(self.selected);
+    }
+
+    fn collect_entries(&self, dir: &Path, depth: usize, entries: &mut Vec<Entry>) {
+        let Ok(read_dir) = fs::read_dir(dir) else {
+            return;
+        };
+
+        let mut children: Vec<Entry> = read_dir
+            .filter_map(Result::ok)
+            .filter(|entry| self.show_hidden || !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| Entry {
+                is_dir: entry.path().is_dir(),
+                path: entry.path(),
+                depth,
+            })
+            .collect();
+        children.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+
+        for child in children {
+            let expand = child.is_dir && self.expanded.contains(&child.path);
+            let path = child.path.clone();
+            entries.push(child);
+            if expand {
+                self.collect_entries(&path, depth.saturating_add(1), entries);
+            }
+        }
+    }
+
+    fn selected_entry(&self) -> Option<&Entry> {
+        self.entries.get(self.selected)
+    }
+
+    // The directory new files get created in: the selected directory, or the one containing the selected file
+    fn target_directory(&self) -> PathBuf {
+        match self.selected_entry() {
+            Some(entry) if entry.is_dir => entry.path.clone(),
+            Some(entry) => entry
+                .path
+                .parent()
+                .map_or_else(|| self.root.clone(), Path::to_path_buf),
+            None => self.root.clone(),
+        }
+    }
+
+    fn visible_entries(&self) -> usize {
+        // the first row shows the root directory
+        self.size.height.saturating_sub(1)
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.entries.len().saturating_sub(1));
+
+        let visible = self.visible_entries().max(1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll.saturating_add(visible) {
+            self.scroll = self.selected.saturating_add(1).saturating_sub(visible);
+        }
+        self.set_needs_redraw(true);
+    }
+
+    fn set_expanded(&mut self, expanded: bool) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        if !entry.is_dir {
+            return;
+        }
+
+        let path = entry.path.clone();
+        if expanded {
+            self.expanded.insert(path);
+        } else {
+            self.expanded.remove(&path);
+        }
+        self.refresh();
+    }
+
+    fn collapse_or_select_parent(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+
+        if entry.is_dir && self.expanded.contains(&entry.path) {
+            self.set_expanded(false);
+        } else if let Some(parent) = entry.path.parent() {
+            if let Some(index) = self.entries.iter().position(|entry| entry.path == parent) {
+                self.select(index);
+            }
+        }
+    }
+
+    fn activate(&mut self) -> ExplorerAction {
+        match self.selected_entry() {
+            Some(entry) if entry.is_dir => {
+                let expanded = self.expanded.contains(&entry.path);
+                self.set_expanded(!expanded);
+                ExplorerAction::None
+            }
+            Some(entry) => ExplorerAction::Open(entry.path.clone()),
+            None => ExplorerAction::None,
+        }
+    }
+
+    fn go_to_parent_root(&mut self) {
+        if let Some(parent) = self.root.parent().map(Path::to_path_buf) {
+            self.set_root(&parent);
+        }
+    }
+
+    pub fn handle_command(&mut self, command: Command) -> ExplorerAction {
+        let page = self.visible_entries().max(1);
+
+        match command {
+            Command::Move(MoveCommand::Up) => self.select(self.selected.saturating_sub(1)),
+            Command::Move(MoveCommand::Down) => self.select(self.selected.saturating_add(1)),
+            Command::Move(MoveCommand::PageUp) => self.select(self.selected.saturating_sub(page)),
+            Command::Move(MoveCommand::PageDown) => self.select(self.selected.saturating_add(page)),
+            Command::Move(MoveCommand::Home) => self.select(0),
+            Command::Move(MoveCommand::End) => self.select(self.entries.len()),
+            Command::Move(MoveCommand::Right) => self.set_expanded(true),
+            Command::Move(MoveCommand::Left) => self.collapse_or_select_parent(),
+            Command::Edit(EditCommand::InsertNewline) => return self.activate(),
+            Command::Edit(EditCommand::Delete) => self.go_to_parent_root(),
+            Command::Edit(EditCommand::Insert('n')) => return ExplorerAction::Create(self.target_directory()),
+            Command::Edit(EditCommand::Insert('r')) => {
+                if let Some(entry) = self.selected_entry() {
+                    return ExplorerAction::Rename(entry.path.clone());
+                }
+            }
+            Command::Edit(EditCommand::Insert('d')) => {
+                if let Some(entry) = self.selected_entry() {
+                    return ExplorerAction::Delete(entry.path.clone());
+                }
+            }
+            Command::Edit(EditCommand::Insert('e')) => return ExplorerAction::EditDirectory(self.target_directory()),
+            Command::Edit(EditCommand::Insert('.')) => {
+                self.show_hidden = !self.show_hidden;
+                self.refresh();
+            }
+            Command::System(SystemCommand::Dismiss) => return ExplorerAction::Unfocus,
+            _ => {}
+        }
+
+        ExplorerAction::None
+    }
+
+    pub fn caret_position(&self) -> Position {
+        Position {
+            row: self.selected.saturating_sub(self.scroll).saturating_add(1),
+            col: 0,
+        }
+    }
+
+    fn entry_label(entry: &Entry, expanded: bool) -> String {
+        let name = entry
+            .path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+        let marker = match (entry.is_dir, expanded) {
+            (true, true) => "▾ ",
+            (true, false) => "▸ ",
+            (false, _) => "  ",
+        };
+        let suffix = if entry.is_dir { "/" } else { "" };
+        format!("{}{marker}{name}{suffix}", "  ".repeat(entry.depth))
+    }
+}
+
+impl UiComponent for FileExplorer {
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.select(self.selected);
+    }
+
+    fn draw(&mut self, origin_row: usize) -> Result<(), Error> {
+        // the last column separates the explorer from the view
+        let width = self.size.width.saturating_sub(1);
+        let print = |row: usize, text: &str| {
+            Terminal::print_at(
+                Position {
+                    row: origin_row.saturating_add(row),
+                    col: 0,
+                },
+                &format!("{text}│"),
+            )
+        };
+
+        let root_name = self
+            .root
+            .file_name()
+            .map_or_else(|| self.root.to_string_lossy(), |name| name.to_string_lossy());
+        print(0, &self.theme.bar(&fit_to_width(&format!(" {root_name}/"), width)))?;
+
+        for row in 0..self.visible_entries() {
+            let index = self.scroll.saturating_add(row);
+            let label = self.entries.get(index).map_or_else(String::new, |entry| {
+                Self::entry_label(entry, self.expanded.contains(&entry.path))
+            });
+            let label = fit_to_width(&label, width);
+            let label = if index == self.selected && self.focused && !self.entries.is_empty() {
+                self.theme.highlighted(&label)
+            } else {
+                label
+            };
+            print(row.saturating_add(1), &label)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use ignore::WalkBuilder;
+
+const MAX_LISTED_FILES: usize = 50_000;
+
+// Splits `path:line` or `path:line:column`, as compilers print positions, into the path and the position.
+// A path that exists as given is never split.
+pub fn split_position(arg: &str) -> (&str, Option<(usize, Option<usize>)>) {
+    if Path::new(arg).exists() {
+        return (arg, None);
+    }
+
+    let trimmed = arg.strip_suffix(':').unwrap_or(arg);
+    let mut parts = trimmed.rsplitn(3, ':');
+    let numbers: Vec<&str> = parts.by_ref().take(2).collect();
+    let rest = parts.next();
+
+    match (numbers.as_slice(), rest) {
+        ([column, line], Some(path)) if !path.is_empty() => match (line.parse(), column.parse()) {
+            (Ok(line), Ok(column)) => (path, Some((line, Some(column)))),
+            (_, Ok(line)) => (&trimmed[..trimmed.len() - column.len() - 1], Some((line, None))),
+            _ => (arg, None),
+        },
+        ([line, path], None) if !path.is_empty() => {
+            line.parse().map_or((arg, None), |line| (*path, Some((line, None))))
+        }
+        _ => (arg, None),
+    }
+}
+
+// Lists the files below `root`, skipping hidden files and anything ignored by `.gitignore`
+pub fn list_files(root: &Path) -> Vec<String> {
+    let mut files: Vec<String> = WalkBuilder::new(root)
+        .hidden(true)
+        .git_ignore(true)
+        .require_git(false)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|file_type| file_type.is_file()))
+        .take(MAX_LISTED_FILES)
+        .filter_map(|entry| {
+            entry
+                .path()
+                .strip_prefix(root)
+                .ok()
+                .and_then(Path::to_str)
+                .map(str::to_string)
+        })
+        .collect();
+
+    files.sort();
+    files
+}
+
+// The first lines of a file, for showing next to a file list
+pub fn preview_file(path: &str, rows: usize) -> Vec<String> {
+    let Ok(file) = File::open(path) else {
+        return vec!["[could not open file]".to_string()];
+    };
+
+    let mut lines = Vec::new();
+    for line in BufReader::new(file).lines().take(rows) {
+        match line {
+            Ok(line) => lines.push(line),
+            Err(_) => return vec!["[binary file]".to_string()],
+        }
+    }
+    lines
+}
+
+// Completes the last component of a partially typed path as far as it is unambiguous.
+// Relative paths are relative to `base`.
+// Returns the completed path and every path the component could still become.
+pub fn complete_path(partial: &str, base: &Path) -> (String, Vec<String>) {
+    let (dir_part, prefix) = partial
+        .rfind('/')
+        .map_or(("", partial), |ind| partial.split_at(ind + 1));
+
+    let dir = if let Some(rest) = dir_part.strip_prefix("~/") {
+        env::var_os("HOME").map_or_else(|| PathBuf::from(dir_part), |home| PathBuf::from(home).join(rest))
+    } else {
+        base.join(dir_part)
+    };
+    let dir = if dir.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        dir
+    };
+
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return (partial.to_string(), Vec::new());
+    };
+
+    let mut names: Vec<String> = read_dir
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let mut name = entry.file_name().to_str()?.to_string();
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            if entry.path().is_dir() {
+                name.push('/');
+            }
+            Some(name)
+        })
+        .collect();
+    names.sort();
+
+    let common = common_prefix(&names);
+
+    let completed = if common.len() > prefix.len() {
+        format!("{dir_part}{common}")
+    } else {
+        partial.to_string()
+    };
+    let candidates = names.into_iter().map(|name| format!("{dir_part}{name}")).collect();
+    (completed, candidates)
+}
+
+// The longest prefix all of the strings start with
+pub fn common_prefix<S: AsRef<str>>(strings: &[S]) -> String {
+    let Some((first, rest)) = strings.split_first() else {
+        return String::new();
+    };
+
+    rest.iter().fold(first.as_ref().to_string(), |common, string| {
+        common
+            .chars()
+            .zip(string.as_ref().chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_position_reads_line_and_column() {
+        assert_eq!(split_position("notes.txt:12"), ("notes.txt", Some((12, None))));
+        assert_eq!(split_position("notes.txt:12:5"), ("notes.txt", Some((12, Some(5)))));
+        assert_eq!(split_position("notes.txt:12:"), ("notes.txt", Some((12, None))));
+        assert_eq!(split_position("notes.txt:12:5:"), ("notes.txt", Some((12, Some(5)))));
+    }
+
+    #[test]
+    fn split_position_keeps_colons_in_the_path() {
+        assert_eq!(split_position("a:b.txt:3"), ("a:b.txt", Some((3, None))));
+        assert_eq!(split_position("a:b.txt:3:4"), ("a:b.txt", Some((3, Some(4)))));
+        assert_eq!(split_position("a:b.txt:x:4"), ("a:b.txt:x", Some((4, None))));
+    }
+
+    #[test]
+    fn split_position_leaves_other_arguments_alone() {
+        assert_eq!(split_position("notes.txt"), ("notes.txt", None));
+        assert_eq!(split_position("notes.txt:x"), ("notes.txt:x", None));
+        assert_eq!(split_position(":12"), (":12", None));
+        assert_eq!(split_position("Cargo.toml"), ("Cargo.toml", None));
+    }
+}
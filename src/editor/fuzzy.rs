@@ -0,0 +1,129 @@
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 12;
+const BOUNDARY_BONUS: i64 = 10;
+const FILE_NAME_BONUS: i64 = 4;
+const GAP_PENALTY: i64 = 3;
+
+// Scores how well `query` matches `candidate` as a case-insensitive subsequence,
+// preferring consecutive runs and matches at the start of words or path segments.
+// Returns None if the query doesn't match at all.
+pub fn score(query: &str, candidate: &str) -> Option<i64> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let original: Vec<char> = candidate.chars().collect();
+    let lowered: Vec<char> = original
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let file_name_start = original
+        .iter()
+        .rposition(|c| *c == '/' || *c == '\\')
+        .map_or(0, |ind| ind + 1);
+    let bonus = |ind: usize| {
+        let mut bonus = 0;
+        let starts_word = ind == 0
+            || !original[ind - 1].is_alphanumeric()
+            || (original[ind - 1].is_lowercase() && original[ind].is_uppercase());
+        if starts_word {
+            bonus += BOUNDARY_BONUS;
+        }
+        if ind >= file_name_start {
+            bonus += FILE_NAME_BONUS;
+        }
+        bonus
+    };
+
+    // best[j] holds the best score of the query prefix matched so far, ending exactly at candidate index j
+    let mut best: Vec<Option<i64>> = vec![None; lowered.len()];
+    for (query_ind, query_char) in query.iter().enumerate() {
+        let mut next = vec![None; lowered.len()];
+        let mut best_before: Option<i64> = None;
+
+        for (ind, candidate_char) in lowered.iter().enumerate() {
+            if candidate_char == query_char {
+                let from_start = (query_ind == 0).then_some(0);
+                let consecutive = ind
+                    .checked_sub(1)
+                    .and_then(|prev| best[prev])
+                    .map(|score| score + CONSECUTIVE_BONUS);
+                let after_gap = best_before.map(|score| score - GAP_PENALTY);
+
+                next[ind] = [from_start, consecutive, after_gap]
+                    .into_iter()
+                    .flatten()
+                    .max()
+                    .map(|score| score + MATCH_SCORE + bonus(ind));
+            }
+
+            // only matches that end before the current index can be extended after a gap
+            if let Some(score) = ind.checked_sub(1).and_then(|prev| best[prev]) {
+                best_before = Some(best_before.map_or(score, |best| best.max(score)));
+            }
+        }
+        best = next;
+    }
+
+    let length_penalty = i64::try_from(lowered.len()).unwrap_or(i64::MAX) / 8;
+    best.into_iter().flatten().max().map(|score| score - length_penalty)
+}
+
+// Returns the indices of the matching candidates, best match first
+pub fn rank<T: AsRef<str>>(query: &str, candidates: &[T]) -> Vec<usize> {
+    let mut scored: Vec<(i64, usize)> = candidates
+        .iter()
+        .enumerate()
+        .filter_map(|(ind, candidate)| score(query, candidate.as_ref()).map(|score| (score, ind)))
+        .collect();
+
+    scored.sort_by(|(score_a, ind_a), (score_b, ind_b)| score_b.cmp(score_a).then(ind_a.cmp(ind_b)));
+    scored.into_iter().map(|(_, ind)| ind).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked<'a>(query: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+        rank(query, candidates).into_iter().map(|ind| candidates[ind]).collect()
+    }
+
+    #[test]
+    fn matches_subsequences_ignoring_case() {
+        assert!(score("edr", "src/editor.rs").is_some());
+        assert!(score("EDITOR", "src/editor.rs").is_some());
+        assert!(score("ser", "src/editor.rs").is_some());
+        // the letters have to come in order
+        assert_eq!(score("rde", "src/editor.rs"), None);
+        assert_eq!(score("xyz", "src/editor.rs"), None);
+        assert_eq!(score("editors", "editor"), None);
+        assert_eq!(score("", "anything"), Some(0));
+    }
+
+    #[test]
+    fn prefers_runs_word_starts_and_file_names() {
+        // a consecutive run over the same letters spread out
+        assert!(score("edit", "src/edit.rs") > score("edit", "src/e_d_i_t.rs"));
+        // the start of a word, also in camelCase, over the middle of one
+        assert!(score("b", "foo_bar") > score("b", "foobar"));
+        assert!(score("b", "fooBar") > score("b", "foobar"));
+        // the file name over the directories above it
+        assert!(score("view", "src/view/main.rs") < score("view", "src/main/view.rs"));
+        // shorter candidates when all else is equal
+        assert!(score("main", "main.rs") > score("main", "main_window_controller.rs"));
+    }
+
+    #[test]
+    fn ranks_best_matches_first() {
+        let candidates = ["src/editor/view/line.rs", "README.md", "src/editor/view.rs", "src/editor.rs"];
+        assert_eq!(ranked("view", &candidates), ["src/editor/view.rs", "src/editor/view/line.rs"]);
+        assert_eq!(ranked("edrs", &candidates)[0], "src/editor.rs");
+        assert!(ranked("zzz", &candidates).is_empty());
+        // ties keep the order of the candidates
+        assert_eq!(ranked("", &candidates), candidates);
+        assert_eq!(ranked("a", &["b/a", "c/a"]), ["b/a", "c/a"]);
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Error, Write},
+};
+
+use log::error;
+
+use super::appdirs;
+
+const MAX_HISTORY_ENTRIES: usize = 100;
+const HISTORY_FILE_NAME: &str = "prompt_history";
+
+// What was entered into each kind of prompt, oldest first
+#[derive(Default)]
+pub struct PromptHistory {
+    entries: HashMap<String, Vec<String>>,
+}
+
+impl PromptHistory {
+    pub fn load() -> Self {
+        let content = appdirs::state_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(HISTORY_FILE_NAME)).ok())
+            .unwrap_or_default();
+
+        let mut history = Self::default();
+        for line in content.lines() {
+            if let Some((kind, entry)) = line.split_once('\t') {
+                history.push(kind, entry);
+            }
+        }
+        history
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let Some(dir) = appdirs::state_dir() else {
+            return Ok(());
+        };
+        fs::create_dir_all(&dir)?;
+
+        let mut kinds: Vec<&String> = self.entries.keys().collect();
+        kinds.sort();
+
+        let mut file = File::create(dir.join(HISTORY_FILE_NAME))?;
+        for kind in kinds {
+            for entry in &self.entries[kind] {
+                writeln!(file, "{kind}\t{entry}")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, kind: &str, entry: &str) {
+        if entry.trim().is_empty() {
+            return;
+        }
+
+        let entries = self.entries.entry(kind.to_string()).or_default();
+        entries.retain(|existing| existing != entry);
+        entries.push(entry.to_string());
+
+        let excess = entries.len().saturating_sub(MAX_HISTORY_ENTRIES);
+        entries.drain(..excess);
+    }
+
+    // Adds the entry as the most recent one for the kind of prompt, dropping any earlier duplicate
+    pub fn add(&mut self, kind: &str, entry: &str) {
+        self.push(kind, entry);
+
+        if let Err(err) = self.save() {
+            error!("Could not save prompt history: {err:?}");
+        }
+    }
+
+    pub fn entries(&self, kind: &str) -> Vec<String> {
+        self.entries.get(kind).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_entries_per_kind_oldest_first() {
+        let mut history = PromptHistory::default();
+        history.push("path", "a.txt");
+        history.push("command", "w");
+        history.push("path", "b.txt");
+        assert_eq!(history.entries("path"), ["a.txt", "b.txt"]);
+        assert_eq!(history.entries("command"), ["w"]);
+        assert!(history.entries("line").is_empty());
+    }
+
+    #[test]
+    fn moves_repeated_entries_to_the_end() {
+        let mut history = PromptHistory::default();
+        for entry in ["a", "b", "c", "a"] {
+            history.push("command", entry);
+        }
+        assert_eq!(history.entries("command"), ["b", "c", "a"]);
+    }
+
+    #[test]
+    fn skips_blank_entries() {
+        let mut history = PromptHistory::default();
+        history.push("command", "");
+        history.push("command", "   ");
+        assert!(history.entries("command").is_empty());
+    }
+
+    #[test]
+    fn drops_the_oldest_entries_past_the_limit() {
+        let mut history = PromptHistory::default();
+        for line in 0..MAX_HISTORY_ENTRIES + 5 {
+            history.push("line", &line.to_string());
+        }
+        let entries = history.entries("line");
+        assert_eq!(entries.len(), MAX_HISTORY_ENTRIES);
+        assert_eq!(entries.first().map(String::as_str), Some("5"));
+        assert_eq!(entries.last(), Some(&(MAX_HISTORY_ENTRIES + 4).to_string()));
+    }
+}
@@ -0,0 +1,40 @@
+use std::collections::VecDeque;
+
+// How many kills are kept before the oldest ones get dropped
+const MAX_ENTRIES: usize = 60;
+
+// Text that was killed, newest first, for yanking it back
+#[derive(Default)]
+pub struct KillRing {
+    entries: VecDeque<String>,
+    // which entry the last yank inserted, moved along by yank-pop
+    yank_index: usize,
+}
+
+impl KillRing {
+    // Adds a new entry, or extends the newest one when the previous command was a kill too
+    pub fn kill(&mut self, text: String, append: bool) {
+        match self.entries.front_mut() {
+            Some(newest) if append => newest.push_str(&text),
+            _ => {
+                self.entries.push_front(text);
+                self.entries.truncate(MAX_ENTRIES);
+            }
+        }
+        self.yank_index = 0;
+    }
+
+    pub fn yank(&mut self) -> Option<&str> {
+        self.yank_index = 0;
+        self.entries.front().map(String::as_str)
+    }
+
+    // The entry before the one yanked last, wrapping around to the newest after the oldest
+    pub fn yank_pop(&mut self) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        self.yank_index = (self.yank_index + 1) % self.entries.len();
+        self.entries.get(self.yank_index).map(String::as_str)
+    }
+}
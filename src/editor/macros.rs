@@ -0,0 +1,123 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File},
+    io::{Error, Write},
+};
+
+use log::error;
+
+use super::{appdirs, command::keymap::KeyChord};
+
+const MACROS_FILE_NAME: &str = "macros";
+
+// How often to play a macro
+#[derive(Clone, Copy)]
+pub enum Repeat {
+    Times(usize),
+    // until a run doesn't move the caret down, e.g. once every line below it has been processed
+    ToEnd,
+}
+
+// Keyboard macros: the one being recorded, the one recorded last, and the ones saved under a name.
+// Macros are the keys as they were pressed, before vim mode or the keymap make commands of them, so
+// playing them back goes through the same steps.
+#[derive(Default)]
+pub struct Macros {
+    recording: Option<Vec<KeyChord>>,
+    last: Vec<KeyChord>,
+    saved: BTreeMap<String, Vec<KeyChord>>,
+}
+
+impl Macros {
+    pub fn load() -> Self {
+        let content = appdirs::state_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(MACROS_FILE_NAME)).ok())
+            .unwrap_or_default();
+
+        let mut macros = Self::default();
+        // a macro missing one of its keys would do something else than what was recorded
+        let mut broken = Vec::new();
+        for line in content.lines() {
+            let Some((name, key)) = line.split_once('\t') else {
+                continue;
+            };
+            match key.parse() {
+                Ok(key) => macros.saved.entry(name.to_string()).or_default().push(key),
+                Err(err) => {
+                    error!("Skipping macro {name}: {err}");
+                    broken.push(name.to_string());
+                }
+            }
+        }
+        for name in broken {
+            macros.saved.remove(&name);
+        }
+        macros
+    }
+
+    fn save_to_disk(&self) -> Result<(), Error> {
+        let Some(dir) = appdirs::state_dir() else {
+            return Ok(());
+        };
+        fs::create_dir_all(&dir)?;
+
+        let mut file = File::create(dir.join(MACROS_FILE_NAME))?;
+        for (name, keys) in &self.saved {
+            for key in keys {
+                writeln!(file, "{name}\t{key}")?;
+            }
+        }
+        Ok(())
+    }
+
+    pub const fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Vec::new());
+    }
+
+    // Ends the recording, which becomes the last macro unless it is empty. Returns how many keys it has.
+    pub fn stop_recording(&mut self) -> usize {
+        let recording = self.recording.take().unwrap_or_default();
+        let len = recording.len();
+        if len > 0 {
+            self.last = recording;
+        }
+        len
+    }
+
+    // Adds the key to the macro being recorded
+    pub fn record(&mut self, key: KeyChord) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push(key);
+        }
+    }
+
+    // Takes the last keys back out of the recording, e.g. the ones that stop it
+    pub fn forget(&mut self, count: usize) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.truncate(recording.len().saturating_sub(count));
+        }
+    }
+
+    // The macro saved under the name, or the last recorded one without a name
+    pub fn get(&self, name: Option<&str>) -> Option<Vec<KeyChord>> {
+        let keys = match name {
+            Some(name) => self.saved.get(name)?,
+            None => &self.last,
+        };
+        (!keys.is_empty()).then(|| keys.clone())
+    }
+
+    // Saves the last recorded macro under the name, also for later sessions
+    pub fn save(&mut self, name: &str) -> Result<(), String> {
+        if self.last.is_empty() {
+            return Err("No macro has been recorded".to_string());
+        }
+        self.saved.insert(name.to_string(), self.last.clone());
+
+        self.save_to_disk().map_err(|err| format!("Could not save macros: {err}"))
+    }
+}
@@ -1,6 +1,6 @@
 mod message;
 
-use std::{io::Error, time::Instant};
+use std::{io::Error, time::{Duration, Instant}};
 
 use message::Message;
 
@@ -9,6 +9,7 @@ use super::{size::Size, terminal::Terminal, uicomponent::UiComponent};
 #[derive(Default, Debug)]
 pub struct MessageBar {
     curr_message: Message,
+    timeout: Duration,
     needs_redraw: bool,
     has_cleared_after_expiry: bool
 }
@@ -21,6 +22,11 @@ impl MessageBar {
         self.has_cleared_after_expiry = false;
         self.set_needs_redraw(true);
     }
+
+    // How long messages stay visible
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
 }
 
 impl UiComponent for MessageBar {
@@ -29,15 +35,15 @@ impl UiComponent for MessageBar {
     }
 
     fn needs_redraw(&self) -> bool {
-        self.needs_redraw || (!self.has_cleared_after_expiry && self.curr_message.is_expired())
+        self.needs_redraw || (!self.has_cleared_after_expiry && self.curr_message.is_expired(self.timeout))
     }
 
     fn draw(&mut self, origin_row: usize) -> Result<(), Error> {
-        if self.curr_message.is_expired() {
+        if self.curr_message.is_expired(self.timeout) {
             self.has_cleared_after_expiry = true;
         }
 
-        let msg = if self.curr_message.is_expired() {
+        let msg = if self.curr_message.is_expired(self.timeout) {
             "" 
         } else {
             &self.curr_message.text
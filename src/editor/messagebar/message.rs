@@ -1,7 +1,5 @@
 use std::time::{Duration, Instant};
 
-const EXPIRE_TIME: Duration = Duration::new(5, 0);
-
 #[derive(Debug)]
 pub struct Message {
     pub text: String,
@@ -9,6 +7,7 @@ pub struct Message {
 }
 
 impl Default for Message {
+    #[allow(clippy::default_trait_access)]
     fn default() -> Self {
         Self { 
             text: Default::default(), 
@@ -18,7 +17,7 @@ impl Default for Message {
 }
 
 impl Message {
-    pub fn is_expired(&self) -> bool {
-        Instant::now().duration_since(self.time) > EXPIRE_TIME
+    pub fn is_expired(&self, timeout: Duration) -> bool {
+        Instant::now().duration_since(self.time) > timeout
     }
 }
\ No newline at end of file
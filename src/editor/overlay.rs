@@ -0,0 +1,127 @@
+mod dialog;
+mod dropdown;
+mod picker;
+mod tooltip;
+
+use std::io::Error;
+
+use super::{
+    command::Command,
+    position::Position,
+    size::Size,
+    terminal::Terminal,
+    theme::Theme,
+    uicomponent::UiComponent,
+    view::line::{DisplayStyle, Line},
+};
+
+pub use dialog::Dialog;
+pub use dropdown::Dropdown;
+pub use picker::Picker;
+pub use tooltip::Tooltip;
+
+pub enum OverlayResponse {
+    // The command was consumed by the overlay, which stays open
+    Handled,
+    // The overlay closes itself and the editor processes the command as usual
+    Closed,
+    // The user confirmed the overlay, choosing the entry at the given index
+    Accepted(usize),
+    // The user closed the overlay without confirming
+    Cancelled,
+}
+
+pub trait Overlay: UiComponent {
+    // Reacts to a command while the overlay is open
+    fn handle_command(&mut self, command: Command) -> OverlayResponse;
+
+    // Where the caret should be placed while the overlay is open, if the overlay wants it
+    fn caret_position(&self) -> Option<Position> {
+        None
+    }
+
+    // The colors to draw highlighted entries in
+    fn set_theme(&mut self, theme: Theme);
+}
+
+#[derive(Clone, Copy)]
+pub enum Anchor {
+    Center,
+    Caret(Position),
+}
+
+impl Anchor {
+    // Top left corner of a box with the given size, kept within the area the overlay is drawn on.
+    // Boxes anchored at the caret open below it, or above it if there is not enough room.
+    pub fn origin(&self, area: Size, content: Size) -> Position {
+        match *self {
+            Self::Center => Position {
+                row: area.height.saturating_sub(content.height) / 2,
+                col: area.width.saturating_sub(content.width) / 2,
+            },
+            Self::Caret(caret) => {
+                let below = caret.row.saturating_add(1);
+                let row = if below.saturating_add(content.height) <= area.height {
+                    below
+                } else {
+                    caret.row.saturating_sub(content.height)
+                };
+                let col = caret.col.min(area.width.saturating_sub(content.width));
+                Position { row, col }
+            }
+        }
+    }
+}
+
+// Truncates or pads the text so that it takes up exactly `width` columns
+pub fn fit_to_width(text: &str, width: usize) -> String {
+    let line = Line::from(text);
+    let mut result = line.get(0..width, DisplayStyle::default());
+    let used = Line::from(result.as_str()).width(DisplayStyle::default());
+    result.push_str(&" ".repeat(width.saturating_sub(used)));
+    result
+}
+
+pub struct FrameRow<'a> {
+    pub text: &'a str,
+    pub highlighted: bool,
+}
+
+// Draws a bordered box with an optional title. `origin` is relative to the area the overlay lives in,
+// which starts at `origin_row` on screen.
+pub fn draw_frame(
+    origin_row: usize,
+    origin: Position,
+    inner_width: usize,
+    title: &str,
+    rows: &[FrameRow],
+    theme: Theme,
+) -> Result<(), Error> {
+    let top = origin_row.saturating_add(origin.row);
+    let title = fit_to_width(title, inner_width);
+    let title = title.trim_end();
+    let border = "─".repeat(inner_width.saturating_sub(Line::from(title).width(DisplayStyle::default())));
+
+    Terminal::print_at(
+        Position { row: top, col: origin.col },
+        &format!("┌{title}{border}┐"),
+    )?;
+
+    for (ind, row) in rows.iter().enumerate() {
+        let text = fit_to_width(row.text, inner_width);
+        let text = if row.highlighted {
+            theme.highlighted(&text)
+        } else {
+            text
+        };
+        Terminal::print_at(
+            Position { row: top.saturating_add(ind).saturating_add(1), col: origin.col },
+            &format!("│{text}│"),
+        )?;
+    }
+
+    Terminal::print_at(
+        Position { row: top.saturating_add(rows.len()).saturating_add(1), col: origin.col },
+        &format!("└{}┘", "─".repeat(inner_width)),
+    )
+}
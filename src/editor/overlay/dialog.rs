@@ -0,0 +1,133 @@
+use std::io::Error;
+
+use crate::editor::{
+    command::{edit::EditCommand, moves::MoveCommand, system::SystemCommand, Command},
+    size::Size,
+    theme::Theme,
+    uicomponent::UiComponent,
+    view::line::{DisplayStyle, Line},
+};
+
+use super::{draw_frame, Anchor, FrameRow, Overlay, OverlayResponse};
+
+pub struct Dialog {
+    title: String,
+    body: Vec<String>,
+    scroll: usize,
+    size: Size,
+    needs_redraw: bool,
+    theme: Theme,
+}
+
+impl Dialog {
+    // A dialog asking a yes/no question, accepted with y or Enter
+    pub fn confirm(title: &str, body: Vec<String>) -> Self {
+        Self {
+            title: format!(" {title} "),
+            body,
+            scroll: 0,
+            size: Size::default(),
+            needs_redraw: true,
+            theme: Theme::default(),
+        }
+    }
+
+    const fn footer() -> &'static str {
+        "[y] Yes  [n] No"
+    }
+
+    fn visible_body_rows(&self) -> usize {
+        // two rows for the borders and one for the footer
+        self.body.len().min(self.size.height.saturating_sub(3))
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        let max_scroll = self.body.len().saturating_sub(self.visible_body_rows());
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max_scroll);
+        self.set_needs_redraw(true);
+    }
+}
+
+impl Overlay for Dialog {
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn handle_command(&mut self, command: Command) -> OverlayResponse {
+        let page = self.visible_body_rows().max(1) as isize;
+
+        match command {
+            Command::Move(MoveCommand::Up) => self.scroll_by(-1),
+            Command::Move(MoveCommand::Down) => self.scroll_by(1),
+            Command::Move(MoveCommand::PageUp) => self.scroll_by(-page),
+            Command::Move(MoveCommand::PageDown) => self.scroll_by(page),
+            Command::System(SystemCommand::Dismiss) | Command::Edit(EditCommand::Insert('n' | 'N')) => {
+                return OverlayResponse::Cancelled
+            }
+            Command::Edit(EditCommand::Insert('y' | 'Y') | EditCommand::InsertNewline) => {
+                return OverlayResponse::Accepted(0)
+            }
+            _ => {}
+        }
+
+        OverlayResponse::Handled
+    }
+}
+
+impl UiComponent for Dialog {
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.scroll_by(0);
+    }
+
+    fn draw(&mut self, origin_row: usize) -> Result<(), Error> {
+        let content_width = self
+            .body
+            .iter()
+            .map(|line| Line::from(line.as_str()).width(DisplayStyle::default()))
+            .chain([Line::from(self.title.as_str()).width(DisplayStyle::default()), Self::footer().len()])
+            .max()
+            .unwrap_or(0);
+        // one column of padding on each side, plus the borders
+        let inner_width = content_width
+            .saturating_add(2)
+            .min(self.size.width.saturating_sub(2));
+
+        let visible = self.visible_body_rows();
+        let padded: Vec<String> = self
+            .body
+            .iter()
+            .skip(self.scroll)
+            .take(visible)
+            .map(|line| format!(" {line}"))
+            .chain([format!(" {}", Self::footer())])
+            .collect();
+        let footer_row = padded.len().saturating_sub(1);
+        let rows: Vec<FrameRow> = padded
+            .iter()
+            .enumerate()
+            .map(|(ind, text)| FrameRow {
+                text,
+                highlighted: ind == footer_row,
+            })
+            .collect();
+
+        let origin = Anchor::Center.origin(
+            self.size,
+            Size {
+                height: rows.len().saturating_add(2),
+                width: inner_width.saturating_add(2),
+            },
+        );
+        draw_frame(origin_row, origin, inner_width, &self.title, &rows, self.theme)
+    }
+}
@@ -0,0 +1,126 @@
+use std::io::Error;
+
+use crate::editor::{
+    command::{edit::EditCommand, moves::MoveCommand, system::SystemCommand, Command},
+    size::Size,
+    theme::Theme,
+    uicomponent::UiComponent,
+    view::line::{DisplayStyle, Line},
+};
+
+use super::{draw_frame, Anchor, FrameRow, Overlay, OverlayResponse};
+
+const MAX_VISIBLE_ITEMS: usize = 8;
+
+pub struct Dropdown {
+    items: Vec<String>,
+    selected: usize,
+    scroll: usize,
+    anchor: Anchor,
+    size: Size,
+    needs_redraw: bool,
+    theme: Theme,
+}
+
+impl Dropdown {
+    pub fn new(items: Vec<String>, anchor: Anchor) -> Self {
+        Self {
+            items,
+            selected: 0,
+            scroll: 0,
+            anchor,
+            size: Size::default(),
+            needs_redraw: true,
+            theme: Theme::default(),
+        }
+    }
+
+    fn visible_items(&self) -> usize {
+        self.items
+            .len()
+            .min(MAX_VISIBLE_ITEMS)
+            .min(self.size.height.saturating_sub(2))
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+
+        let visible = self.visible_items().max(1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll.saturating_add(visible) {
+            self.scroll = self.selected.saturating_add(1).saturating_sub(visible);
+        }
+        self.set_needs_redraw(true);
+    }
+}
+
+impl Overlay for Dropdown {
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    fn handle_command(&mut self, command: Command) -> OverlayResponse {
+        let page = self.visible_items().max(1);
+
+        match command {
+            Command::Move(MoveCommand::Up) => self.select(self.selected.saturating_sub(1)),
+            Command::Move(MoveCommand::Down) => self.select(self.selected.saturating_add(1)),
+            Command::Move(MoveCommand::PageUp) => self.select(self.selected.saturating_sub(page)),
+            Command::Move(MoveCommand::PageDown) => self.select(self.selected.saturating_add(page)),
+            Command::Edit(EditCommand::InsertNewline | EditCommand::Insert('\t')) => {
+                return OverlayResponse::Accepted(self.selected)
+            }
+            Command::System(SystemCommand::Dismiss) => return OverlayResponse::Cancelled,
+            _ => return OverlayResponse::Closed,
+        }
+
+        OverlayResponse::Handled
+    }
+}
+
+impl UiComponent for Dropdown {
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.select(self.selected);
+    }
+
+    fn draw(&mut self, origin_row: usize) -> Result<(), Error> {
+        let inner_width = self
+            .items
+            .iter()
+            .map(|item| Line::from(item.as_str()).width(DisplayStyle::default()))
+            .max()
+            .unwrap_or(0)
+            .min(self.size.width.saturating_sub(2));
+
+        let rows: Vec<FrameRow> = self
+            .items
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(self.visible_items())
+            .map(|(ind, item)| FrameRow {
+                text: item,
+                highlighted: ind == self.selected,
+            })
+            .collect();
+
+        let origin = self.anchor.origin(
+            self.size,
+            Size {
+                height: rows.len().saturating_add(2),
+                width: inner_width.saturating_add(2),
+            },
+        );
+        draw_frame(origin_row, origin, inner_width, "", &rows, self.theme)
+    }
+}
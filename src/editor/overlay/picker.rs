@@ -0,0 +1,237 @@
+use std::io::Error;
+
+use crate::editor::{
+    command::{edit::EditCommand, moves::MoveCommand, system::SystemCommand, Command},
+    fuzzy,
+    position::Position,
+    size::Size,
+    terminal::Terminal,
+    theme::Theme,
+    uicomponent::UiComponent,
+    view::line::{DisplayStyle, Line},
+};
+
+use super::{fit_to_width, Anchor, Overlay, OverlayResponse};
+
+// Produces the preview lines for an item, given how many rows are available
+pub type Previewer = fn(&str, usize) -> Vec<String>;
+
+// A centered list that is fuzzy-filtered by what the user types,
+// with an optional preview of the highlighted item next to it.
+pub struct Picker {
+    title: String,
+    query: Line,
+    items: Vec<String>,
+    matches: Vec<usize>,
+    selected: usize,
+    scroll: usize,
+    previewer: Option<Previewer>,
+    preview: Vec<String>,
+    size: Size,
+    needs_redraw: bool,
+    theme: Theme,
+}
+
+impl Picker {
+    pub fn new(title: &str, items: Vec<String>, previewer: Option<Previewer>) -> Self {
+        let mut picker = Self {
+            title: format!(" {title} "),
+            query: Line::default(),
+            matches: (0..items.len()).collect(),
+            items,
+            selected: 0,
+            scroll: 0,
+            previewer,
+            preview: Vec::new(),
+            size: Size::default(),
+            needs_redraw: true,
+            theme: Theme::default(),
+        };
+        picker.refresh_preview();
+        picker
+    }
+
+    // Starts out filtered by the query, as if it had been typed
+    pub fn with_query(mut self, query: &str) -> Self {
+        self.query = Line::from(query);
+        self.filter();
+        self
+    }
+
+    fn box_size(&self) -> Size {
+        let width = if self.previewer.is_some() {
+            self.size.width.saturating_mul(9) / 10
+        } else {
+            self.size.width.saturating_mul(4) / 5
+        };
+        Size {
+            height: self.size.height.saturating_mul(4) / 5,
+            width,
+        }
+    }
+
+    fn list_width(&self) -> usize {
+        let inner_width = self.box_size().width.saturating_sub(2);
+        if self.previewer.is_some() {
+            inner_width.saturating_sub(1) * 2 / 5
+        } else {
+            inner_width
+        }
+    }
+
+    fn visible_items(&self) -> usize {
+        // borders, query row and separator
+        self.box_size().height.saturating_sub(4)
+    }
+
+    fn filter(&mut self) {
+        let query = self.query.to_string();
+        self.matches = fuzzy::rank(&query, &self.items);
+        self.scroll = 0;
+        self.select(0);
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.matches.len().saturating_sub(1));
+
+        let visible = self.visible_items().max(1);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll.saturating_add(visible) {
+            self.scroll = self.selected.saturating_add(1).saturating_sub(visible);
+        }
+        self.refresh_preview();
+        self.set_needs_redraw(true);
+    }
+
+    fn refresh_preview(&mut self) {
+        let rows = self.visible_items();
+        self.preview = match (self.previewer, self.matches.get(self.selected)) {
+            (Some(previewer), Some(&item)) => previewer(&self.items[item], rows),
+            _ => Vec::new(),
+        };
+    }
+}
+
+impl Overlay for Picker {
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    fn handle_command(&mut self, command: Command) -> OverlayResponse {
+        let page = self.visible_items().max(1);
+
+        match command {
+            Command::Move(MoveCommand::Up) => self.select(self.selected.saturating_sub(1)),
+            Command::Move(MoveCommand::Down) => self.select(self.selected.saturating_add(1)),
+            Command::Move(MoveCommand::PageUp) => self.select(self.selected.saturating_sub(page)),
+            Command::Move(MoveCommand::PageDown) => self.select(self.selected.saturating_add(page)),
+            Command::Edit(EditCommand::Insert(c)) => {
+                self.query.append_char(c);
+                self.filter();
+            }
+            Command::Edit(EditCommand::Delete) => {
+                self.query.delete_last();
+                self.filter();
+            }
+            Command::Edit(EditCommand::InsertNewline) => {
+                if let Some(&item) = self.matches.get(self.selected) {
+                    return OverlayResponse::Accepted(item);
+                }
+            }
+            Command::System(SystemCommand::Dismiss) => return OverlayResponse::Cancelled,
+            _ => {}
+        }
+
+        OverlayResponse::Handled
+    }
+
+    fn caret_position(&self) -> Option<Position> {
+        let origin = Anchor::Center.origin(self.size, self.box_size());
+        let col = origin
+            .col
+            .saturating_add(3)
+            .saturating_add(self.query.width(DisplayStyle::default()))
+            .min(origin.col.saturating_add(self.box_size().width.saturating_sub(2)));
+        Some(Position {
+            row: origin.row.saturating_add(1),
+            col,
+        })
+    }
+}
+
+impl UiComponent for Picker {
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.select(self.selected);
+    }
+
+    fn draw(&mut self, origin_row: usize) -> Result<(), Error> {
+        let box_size = self.box_size();
+        if box_size.height < 5 || box_size.width < 10 {
+            return Ok(());
+        }
+
+        let origin = Anchor::Center.origin(self.size, box_size);
+        let inner_width = box_size.width.saturating_sub(2);
+        let list_width = self.list_width();
+        let top = origin_row.saturating_add(origin.row);
+        let print = |row: usize, text: &str| {
+            Terminal::print_at(
+                Position {
+                    row: top.saturating_add(row),
+                    col: origin.col,
+                },
+                text,
+            )
+        };
+
+        let title = fit_to_width(&self.title, inner_width);
+        let title = title.trim_end();
+        let counter = format!(" {}/{} ", self.matches.len(), self.items.len());
+        let border_width = inner_width
+            .saturating_sub(Line::from(title).width(DisplayStyle::default()))
+            .saturating_sub(counter.len());
+        print(0, &format!("┌{title}{}{counter}┐", "─".repeat(border_width)))?;
+
+        let query = fit_to_width(&format!("> {}", self.query), inner_width);
+        print(1, &format!("│{query}│"))?;
+        print(2, &format!("├{}┤", "─".repeat(inner_width)))?;
+
+        for row in 0..self.visible_items() {
+            let match_ind = self.scroll.saturating_add(row);
+            let entry = self
+                .matches
+                .get(match_ind)
+                .map_or("", |&item| self.items[item].as_str());
+            let entry = fit_to_width(&format!(" {entry}"), list_width);
+            let entry = if match_ind == self.selected && !self.matches.is_empty() {
+                self.theme.highlighted(&entry)
+            } else {
+                entry
+            };
+
+            let text = if self.previewer.is_some() {
+                let preview_width = inner_width.saturating_sub(list_width).saturating_sub(1);
+                let preview = self.preview.get(row).map_or("", String::as_str);
+                format!("{entry}│{}", fit_to_width(preview, preview_width))
+            } else {
+                entry
+            };
+            print(row.saturating_add(3), &format!("│{text}│"))?;
+        }
+
+        print(
+            box_size.height.saturating_sub(1),
+            &format!("└{}┘", "─".repeat(inner_width)),
+        )
+    }
+}
@@ -0,0 +1,84 @@
+use std::io::Error;
+
+use crate::editor::{
+    command::Command,
+    position::Position,
+    size::Size,
+    terminal::Terminal,
+    theme::Theme,
+    uicomponent::UiComponent,
+    view::line::{DisplayStyle, Line},
+};
+
+use super::{fit_to_width, Anchor, Overlay, OverlayResponse};
+
+// A borderless hint that closes as soon as the user does anything else
+pub struct Tooltip {
+    lines: Vec<String>,
+    anchor: Anchor,
+    size: Size,
+    needs_redraw: bool,
+    theme: Theme,
+}
+
+impl Tooltip {
+    pub fn new(lines: Vec<String>, anchor: Anchor) -> Self {
+        Self {
+            lines,
+            anchor,
+            size: Size::default(),
+            needs_redraw: true,
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl Overlay for Tooltip {
+    fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    fn handle_command(&mut self, _: Command) -> OverlayResponse {
+        OverlayResponse::Closed
+    }
+}
+
+impl UiComponent for Tooltip {
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+    }
+
+    fn draw(&mut self, origin_row: usize) -> Result<(), Error> {
+        let width = self
+            .lines
+            .iter()
+            .map(|line| Line::from(line.as_str()).width(DisplayStyle::default()).saturating_add(2))
+            .max()
+            .unwrap_or(0)
+            .min(self.size.width);
+        let height = self.lines.len().min(self.size.height);
+
+        let origin = self.anchor.origin(self.size, Size { height, width });
+
+        for (ind, line) in self.lines.iter().take(height).enumerate() {
+            let text = fit_to_width(&format!(" {line}"), width);
+            Terminal::print_at(
+                Position {
+                    row: origin_row.saturating_add(origin.row).saturating_add(ind),
+                    col: origin.col,
+                },
+                &self.theme.highlighted(&text),
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -13,8 +13,21 @@ impl Position {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Location {
     pub grapheme_index: usize,
     pub row: usize,
 }
+
+// Locations are ordered as they appear in the text, row first
+impl Ord for Location {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.row, self.grapheme_index).cmp(&(other.row, other.grapheme_index))
+    }
+}
+
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
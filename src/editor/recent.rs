@@ -0,0 +1,107 @@
+use std::{
+    env,
+    fs::{self, File},
+    io::{Error, Write},
+    path::{Path, PathBuf},
+};
+
+use log::error;
+
+use super::{appdirs, position::Location};
+
+const MAX_RECENT_FILES: usize = 50;
+const RECENT_FILES_NAME: &str = "recent_files";
+
+pub struct RecentFile {
+    pub path: PathBuf,
+    pub location: Location,
+}
+
+// The most recently used files, most recent first, along with where the caret was in each of them
+#[derive(Default)]
+pub struct RecentFiles {
+    files: Vec<RecentFile>,
+}
+
+impl RecentFiles {
+    pub fn load() -> Self {
+        let content = appdirs::state_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(RECENT_FILES_NAME)).ok())
+            .unwrap_or_default();
+
+        let files = content
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                let row = parts.next()?.parse().ok()?;
+                let grapheme_index = parts.next()?.parse().ok()?;
+                let path = PathBuf::from(parts.next()?);
+                Some(RecentFile {
+                    path,
+                    location: Location { grapheme_index, row },
+                })
+            })
+            .take(MAX_RECENT_FILES)
+            .collect();
+
+        Self { files }
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let Some(dir) = appdirs::state_dir() else {
+            return Ok(());
+        };
+        fs::create_dir_all(&dir)?;
+
+        let mut file = File::create(dir.join(RECENT_FILES_NAME))?;
+        for recent in &self.files {
+            writeln!(
+                file,
+                "{}\t{}\t{}",
+                recent.location.row,
+                recent.location.grapheme_index,
+                recent.path.display()
+            )?;
+        }
+        Ok(())
+    }
+
+    // Moves the file to the top of the list, remembering where the caret is
+    pub fn remember(&mut self, path: &Path, location: Location) {
+        let Ok(path) = fs::canonicalize(path) else {
+            return;
+        };
+
+        self.files.retain(|file| file.path != path);
+        self.files.insert(0, RecentFile { path, location });
+        self.files.truncate(MAX_RECENT_FILES);
+
+        if let Err(err) = self.save() {
+            error!("Could not save recent files: {err:?}");
+        }
+    }
+
+    pub fn location_of(&self, path: &Path) -> Option<Location> {
+        let path = fs::canonicalize(path).ok()?;
+        self.files
+            .iter()
+            .find(|file| file.path == path)
+            .map(|file| file.location)
+    }
+
+    // The recent files that still exist, relative to the working directory where possible
+    pub fn display_paths(&self) -> Vec<String> {
+        let current_dir = env::current_dir().unwrap_or_default();
+        self.files
+            .iter()
+            .filter(|file| file.path.is_file())
+            .map(|file| {
+                file.path
+                    .strip_prefix(&current_dir)
+                    .unwrap_or(&file.path)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    }
+}
@@ -1,12 +1,13 @@
 use super::{
-    documentstatus::DocumentStatus, size::Size, terminal::Terminal, uicomponent::UiComponent
+    documentstatus::DocumentStatus, size::Size, terminal::Terminal, theme::Theme, uicomponent::UiComponent
 };
 
 #[derive(Default)]
 pub struct StatusBar {
     curr_status: DocumentStatus,
     needs_redraw: bool,
-    size: Size
+    size: Size,
+    theme: Theme,
 }
 
 impl StatusBar {
@@ -16,6 +17,11 @@ impl StatusBar {
             self.set_needs_redraw(true);
         }
     }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.set_needs_redraw(true);
+    }
 }
 
 impl UiComponent for StatusBar {
@@ -32,8 +38,13 @@ impl UiComponent for StatusBar {
         let line_count = self.curr_status.get_line_count_string();
         let modified_indicator = self.curr_status.get_modified_indicator_string();
 
+        let mode = self
+            .curr_status
+            .mode
+            .as_ref()
+            .map_or_else(String::new, |mode| format!("{mode} | "));
         let beginning = format!(
-            "{} - {line_count} {modified_indicator}",
+            "{mode}{} - {line_count} {modified_indicator}",
             self.curr_status.file_name
         );
 
@@ -51,7 +62,8 @@ impl UiComponent for StatusBar {
         };
 
 
-        Terminal::print_inverted_row(origin_row, &to_print)?;
+        let width = self.size.width;
+        Terminal::print_row(origin_row, &self.theme.bar(&format!("{to_print:width$.width$}")))?;
 
         Ok(())
     }
@@ -1,5 +1,5 @@
 use crossterm::{
-    cursor, queue, style::{self, Attribute},
+    cursor, queue, style,
     terminal::{
         self, 
         ClearType, 
@@ -15,6 +15,15 @@ use std::io::{stdout, Error, Write};
 
 use super::{position::Position, size::Size};
 
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaretShape {
+    // whatever the user's terminal is set up to show
+    #[default]
+    Default,
+    Block,
+    Bar,
+}
+
 pub struct Terminal {}
 
 impl Terminal {
@@ -30,6 +39,7 @@ impl Terminal {
     pub fn terminate() -> Result<(), Error> {
         Self::leave_alternate_screen()?;
         Self::enable_line_wrap()?;
+        Self::set_caret_shape(CaretShape::Default)?;
         Self::show_caret()?;
         Self::execute()?;
         terminal::disable_raw_mode()?;
@@ -46,6 +56,7 @@ impl Terminal {
         Ok(())
     }
 
+    #[allow(clippy::cast_possible_truncation)]
     pub fn move_caret_to(position: Position) -> Result<(), Error> {
         Self::queue_command(cursor::MoveTo(position.col as u16, position.row as u16))?;
         Ok(())
@@ -69,6 +80,16 @@ impl Terminal {
         Ok(())
     }
 
+    pub fn set_caret_shape(shape: CaretShape) -> Result<(), Error> {
+        let style = match shape {
+            CaretShape::Default => cursor::SetCursorStyle::DefaultUserShape,
+            CaretShape::Block => cursor::SetCursorStyle::SteadyBlock,
+            CaretShape::Bar => cursor::SetCursorStyle::SteadyBar,
+        };
+        Self::queue_command(style)?;
+        Ok(())
+    }
+
     pub fn print(text: &str) -> Result<(), Error> {
         Self::queue_command(style::Print(text))?;
         Ok(())
@@ -81,18 +102,18 @@ impl Terminal {
         Ok(())
     }
 
-    pub fn print_inverted_row(row: usize, line_text: &str) -> Result<(), Error> {
-        let width = Self::size()?.width;
+    // Like print_row, but leaves everything left of `position.col` untouched
+    pub fn print_row_from(position: Position, text: &str) -> Result<(), Error> {
+        Self::move_caret_to(position)?;
+        Self::queue_command(terminal::Clear(ClearType::UntilNewLine))?;
+        Self::print(text)?;
+        Ok(())
+    }
 
-        Self::print_row(
-            row,
-            &format!(
-                "{}{:width$.width$}{}",
-                Attribute::Reverse,
-                line_text,
-                Attribute::Reset
-            ),
-        )
+    pub fn print_at(position: Position, text: &str) -> Result<(), Error> {
+        Self::move_caret_to(position)?;
+        Self::print(text)?;
+        Ok(())
     }
 
     pub fn execute() -> Result<(), Error> {
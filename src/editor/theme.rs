@@ -0,0 +1,63 @@
+use std::str::FromStr;
+
+use crossterm::style::{Attribute, Color, ResetColor, SetBackgroundColor, SetForegroundColor};
+
+// The colors of the parts of the screen that stand out: the status bar, selections and highlighted
+// entries, and flagged text such as trailing whitespace
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    // reverse video, which follows the terminal's own colors
+    #[default]
+    Default,
+    // white on blue bars, black on cyan selections
+    Blue,
+}
+
+impl Theme {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Blue => "blue",
+        }
+    }
+
+    // The status bar and other bars across the screen
+    pub fn bar(self, text: &str) -> String {
+        match self {
+            Self::Default => format!("{}{text}{}", Attribute::Reverse, Attribute::Reset),
+            Self::Blue => Self::colored(text, Color::White, Color::DarkBlue),
+        }
+    }
+
+    // Selected text, and the highlighted entry of a list
+    pub fn highlighted(self, text: &str) -> String {
+        match self {
+            Self::Default => format!("{}{text}{}", Attribute::Reverse, Attribute::Reset),
+            Self::Blue => Self::colored(text, Color::Black, Color::Cyan),
+        }
+    }
+
+    // Text that draws attention to itself
+    pub fn flagged(self, text: &str) -> String {
+        match self {
+            Self::Default => format!("{}{text}{}", SetBackgroundColor(Color::DarkRed), ResetColor),
+            Self::Blue => Self::colored(text, Color::White, Color::DarkMagenta),
+        }
+    }
+
+    fn colored(text: &str, foreground: Color, background: Color) -> String {
+        format!("{}{}{text}{}", SetForegroundColor(foreground), SetBackgroundColor(background), ResetColor)
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "default" => Ok(Self::Default),
+            "blue" => Ok(Self::Blue),
+            _ => Err(format!("expected default or blue, got {value:?}")),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use unicode_general_category::get_general_category;
+use unicode_width::UnicodeWidthChar;
+
+// The names of the C0 and C1 control characters, which Unicode only gives as aliases
+const C0_CONTROLS: [&str; 32] = [
+    "NULL", "START OF HEADING", "START OF TEXT", "END OF TEXT", "END OF TRANSMISSION", "ENQUIRY", "ACKNOWLEDGE",
+    "ALERT", "BACKSPACE", "CHARACTER TABULATION", "LINE FEED", "LINE TABULATION", "FORM FEED", "CARRIAGE RETURN",
+    "SHIFT OUT", "SHIFT IN", "DATA LINK ESCAPE", "DEVICE CONTROL ONE", "DEVICE CONTROL TWO", "DEVICE CONTROL THREE",
+    "DEVICE CONTROL FOUR", "NEGATIVE ACKNOWLEDGE", "SYNCHRONOUS IDLE", "END OF TRANSMISSION BLOCK", "CANCEL",
+    "END OF MEDIUM", "SUBSTITUTE", "ESCAPE", "INFORMATION SEPARATOR FOUR", "INFORMATION SEPARATOR THREE",
+    "INFORMATION SEPARATOR TWO", "INFORMATION SEPARATOR ONE",
+];
+const C1_CONTROLS: [&str; 32] = [
+    "PADDING CHARACTER", "HIGH OCTET PRESET", "BREAK PERMITTED HERE", "NO BREAK HERE", "INDEX", "NEXT LINE",
+    "START OF SELECTED AREA", "END OF SELECTED AREA", "CHARACTER TABULATION SET",
+    "CHARACTER TABULATION WITH JUSTIFICATION", "LINE TABULATION SET", "PARTIAL LINE FORWARD",
+    "PARTIAL LINE BACKWARD", "REVERSE LINE FEED", "SINGLE SHIFT TWO", "SINGLE SHIFT THREE", "DEVICE CONTROL STRING",
+    "PRIVATE USE ONE", "PRIVATE USE TWO", "SET TRANSMIT STATE", "CANCEL CHARACTER", "MESSAGE WAITING",
+    "START OF GUARDED AREA", "END OF GUARDED AREA", "START OF STRING", "SINGLE GRAPHIC CHARACTER INTRODUCER",
+    "SINGLE CHARACTER INTRODUCER", "CONTROL SEQUENCE INTRODUCER", "STRING TERMINATOR", "OPERATING SYSTEM COMMAND",
+    "PRIVACY MESSAGE", "APPLICATION PROGRAM COMMAND",
+];
+
+// Two keys that stand for a character, as in RFC 1345 and vim: a letter followed by a mark for the accent
+// (`'` acute, `!` grave, `>` circumflex, `?` tilde, `:` diaeresis, `,` cedilla, `-` macron, `(` breve,
+// `<` caron, `.` dot above, `0` ring), and keys that look like the symbol, such as `->` for `→`
+const DIGRAPHS: &[(&str, char)] = &[
+    ("A'", 'Á'), ("A!", 'À'), ("A>", 'Â'), ("A?", 'Ã'), ("A:", 'Ä'), ("A-", 'Ā'), ("A(", 'Ă'), ("A0", 'Å'), ("AA", 'Å'),
+    ("a'", 'á'), ("a!", 'à'), ("a>", 'â'), ("a?", 'ã'), ("a:", 'ä'), ("a-", 'ā'), ("a(", 'ă'), ("a0", 'å'), ("aa", 'å'),
+    ("C'", 'Ć'), ("C>", 'Ĉ'), ("C,", 'Ç'), ("C<", 'Č'), ("C.", 'Ċ'),
+    ("c'", 'ć'), ("c>", 'ĉ'), ("c,", 'ç'), ("c<", 'č'), ("c.", 'ċ'),
+    ("D<", 'Ď'),
+    ("d<", 'ď'),
+    ("E'", 'É'), ("E!", 'È'), ("E>", 'Ê'), ("E:", 'Ë'), ("E-", 'Ē'), ("E(", 'Ĕ'), ("E<", 'Ě'), ("E.", 'Ė'),
+    ("e'", 'é'), ("e!", 'è'), ("e>", 'ê'), ("e:", 'ë'), ("e-", 'ē'), ("e(", 'ĕ'), ("e<", 'ě'), ("e.", 'ė'),
+    ("G>", 'Ĝ'), ("G,", 'Ģ'), ("G(", 'Ğ'), ("G.", 'Ġ'),
+    ("g>", 'ĝ'), ("g,", 'ģ'), ("g(", 'ğ'), ("g.", 'ġ'),
+    ("H>", 'Ĥ'),
+    ("h>", 'ĥ'),
+    ("I'", 'Í'), ("I!", 'Ì'), ("I>", 'Î'), ("I?", 'Ĩ'), ("I:", 'Ï'), ("I-", 'Ī'), ("I(", 'Ĭ'), ("I.", 'İ'),
+    ("i'", 'í'), ("i!", 'ì'), ("i>", 'î'), ("i?", 'ĩ'), ("i:", 'ï'), ("i-", 'ī'), ("i(", 'ĭ'),
+    ("J>", 'Ĵ'),
+    ("j>", 'ĵ'),
+    ("K,", 'Ķ'),
+    ("k,", 'ķ'),
+    ("L'", 'Ĺ'), ("L,", 'Ļ'), ("L<", 'Ľ'),
+    ("l'", 'ĺ'), ("l,", 'ļ'), ("l<", 'ľ'),
+    ("N'", 'Ń'), ("N?", 'Ñ'), ("N,", 'Ņ'), ("N<", 'Ň'),
+    ("n'", 'ń'), ("n?", 'ñ'), ("n,", 'ņ'), ("n<", 'ň'),
+    ("O'", 'Ó'), ("O!", 'Ò'), ("O>", 'Ô'), ("O?", 'Õ'), ("O:", 'Ö'), ("O-", 'Ō'), ("O(", 'Ŏ'),
+    ("o'", 'ó'), ("o!", 'ò'), ("o>", 'ô'), ("o?", 'õ'), ("o:", 'ö'), ("o-", 'ō'), ("o(", 'ŏ'),
+    ("R'", 'Ŕ'), ("R,", 'Ŗ'), ("R<", 'Ř'),
+    ("r'", 'ŕ'), ("r,", 'ŗ'), ("r<", 'ř'),
+    ("S'", 'Ś'), ("S>", 'Ŝ'), ("S,", 'Ş'), ("S<", 'Š'),
+    ("s'", 'ś'), ("s>", 'ŝ'), ("s,", 'ş'), ("s<", 'š'),
+    ("T,", 'Ţ'), ("T<", 'Ť'),
+    ("t,", 'ţ'), ("t<", 'ť'),
+    ("U'", 'Ú'), ("U!", 'Ù'), ("U>", 'Û'), ("U?", 'Ũ'), ("U:", 'Ü'), ("U-", 'Ū'), ("U(", 'Ŭ'), ("U0", 'Ů'),
+    ("u'", 'ú'), ("u!", 'ù'), ("u>", 'û'), ("u?", 'ũ'), ("u:", 'ü'), ("u-", 'ū'), ("u(", 'ŭ'), ("u0", 'ů'),
+    ("W>", 'Ŵ'),
+    ("w>", 'ŵ'),
+    ("Y'", 'Ý'), ("Y>", 'Ŷ'), ("Y:", 'Ÿ'),
+    ("y'", 'ý'), ("y>", 'ŷ'), ("y:", 'ÿ'),
+    ("Z'", 'Ź'), ("Z<", 'Ž'), ("Z.", 'Ż'),
+    ("z'", 'ź'), ("z<", 'ž'), ("z.", 'ż'),
+    ("ss", 'ß'), ("ae", 'æ'), ("AE", 'Æ'), ("oe", 'œ'), ("OE", 'Œ'), ("o/", 'ø'), ("O/", 'Ø'), ("!I", '¡'), ("?I", '¿'),
+    ("a*", 'α'), ("b*", 'β'), ("g*", 'γ'), ("d*", 'δ'), ("e*", 'ε'), ("l*", 'λ'), ("m*", 'μ'), ("p*", 'π'), ("s*", 'σ'),
+    ("t*", 'τ'), ("f*", 'φ'), ("w*", 'ω'), ("D*", 'Δ'), ("P*", 'Π'), ("S*", 'Σ'), ("W*", 'Ω'),
+    ("->", '→'), ("<-", '←'), ("-!", '↑'), ("-v", '↓'), ("<>", '↔'), ("=>", '⇒'), ("==", '⇔'),
+    ("=<", '≤'), (">=", '≥'), ("!=", '≠'), ("?2", '≈'), ("=3", '≡'), ("+-", '±'), ("*X", '×'), ("-:", '÷'),
+    ("00", '∞'), ("RT", '√'), ("FA", '∀'), ("TE", '∃'), ("(-", '∈'), ("DE", '∆'), ("OK", '✓'), ("XX", '✗'),
+    ("DG", '°'), ("Co", '©'), ("Rg", '®'), ("TM", '™'), ("SE", '§'), ("PI", '¶'), ("My", 'µ'),
+    ("Eu", '€'), ("Pd", '£'), ("Ye", '¥'), ("Ct", '¢'),
+    ("12", '½'), ("14", '¼'), ("34", '¾'), ("1S", '¹'), ("2S", '²'), ("3S", '³'),
+    ("-N", '–'), ("-M", '—'), (",.", '…'), (".M", '·'), ("<<", '«'), (">>", '»'), ("NS", '\u{a0}'),
+    ("'6", '‘'), ("'9", '’'), ("\"6", '“'), ("\"9", '”'),
+];
+
+// The Unicode name of the character, e.g. `LATIN SMALL LETTER E` or `ZERO WIDTH JOINER`. Control characters
+// go by their aliases.
+pub fn name(c: char) -> Option<String> {
+    let code = u32::from(c) as usize;
+    match c {
+        '\0'..='\u{1f}' => Some(C0_CONTROLS[code].to_string()),
+        '\u{7f}' => Some("DELETE".to_string()),
+        '\u{80}'..='\u{9f}' => Some(C1_CONTROLS[code - 0x80].to_string()),
+        _ => unicode_names2::name(c).map(|name| name.to_string()),
+    }
+}
+
+// The character with the name or alias, in any case
+pub fn character(name: &str) -> Option<char> {
+    unicode_names2::character(name.trim())
+}
+
+// The two-letter general category of the character, e.g. `Lu` or `Mn`
+pub fn category(c: char) -> &'static str {
+    get_general_category(c).abbreviation()
+}
+
+// One line about the character: its codepoint, category, UTF-8 bytes and name
+pub fn describe(c: char) -> String {
+    let bytes: Vec<String> = c.encode_utf8(&mut [0; 4]).bytes().map(|byte| format!("{byte:02X}")).collect();
+    let name = name(c).unwrap_or_else(|| "(no name)".to_string());
+    format!("U+{:04X}  {}  {:<11}  {name}", u32::from(c), category(c), bytes.join(" "))
+}
+
+pub fn digraph(keys: &str) -> Option<char> {
+    DIGRAPHS.iter().find(|(digraph, _)| *digraph == keys).map(|(_, c)| *c)
+}
+
+// The digraph that stands for the character, the first one listed if there are several
+pub fn digraph_for(c: char) -> Option<&'static str> {
+    static DIGRAPH_FOR: OnceLock<HashMap<char, &'static str>> = OnceLock::new();
+    let digraphs = DIGRAPH_FOR.get_or_init(|| {
+        let mut digraphs = HashMap::new();
+        for &(digraph, c) in DIGRAPHS {
+            digraphs.entry(c).or_insert(digraph);
+        }
+        digraphs
+    });
+    digraphs.get(&c).copied()
+}
+
+// The characters that can be looked up by name, in codepoint order, each with its line in the character picker:
+// the character, its codepoint, its digraph and its name. Ideographs named after their codepoint, such as
+// `CJK UNIFIED IDEOGRAPH-4E00`, are left out, as they are found by codepoint. The list takes a walk through
+// every codepoint, so it is only built once.
+pub fn named_characters() -> &'static [(char, String)] {
+    static NAMED_CHARACTERS: OnceLock<Vec<(char, String)>> = OnceLock::new();
+    NAMED_CHARACTERS.get_or_init(|| {
+        ('\0'..=char::MAX)
+            .filter_map(|c| Some((c, name(c)?)))
+            .filter(|(c, name)| !name.ends_with(&format!("-{:04X}", u32::from(*c))))
+            .map(|(c, name)| {
+                let codepoint = format!("U+{:04X}", u32::from(c));
+                let digraph = digraph_for(c).unwrap_or_default();
+                (c, format!("{}  {codepoint:<7}  {digraph:<2}  {name}", label(c)))
+            })
+            .collect()
+    })
+}
+
+// What was typed to insert a character
+pub enum CharacterQuery {
+    Character(char),
+    // words to look for in the names of characters
+    Name(String),
+}
+
+// Reads a digraph such as `e'`, a codepoint such as `U+2192`, `0x2192` or a bare `2192` (bare codepoints need
+// a digit, so that words such as `face` are looked up as names), or anything else as (part of) a name.
+// Digraphs come first, so `12` is `½` and `00` is `∞`, and those codepoints need their `U+`.
+pub fn parse_query(value: &str) -> Result<CharacterQuery, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("expected a codepoint, digraph or character name".to_string());
+    }
+    if let Some(c) = digraph(value) {
+        return Ok(CharacterQuery::Character(c));
+    }
+
+    let hex = ["U+", "u+", "0x", "0X"]
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .or_else(|| {
+            let is_bare_hex = value.len() <= 6
+                && value.chars().all(|c| c.is_ascii_hexdigit())
+                && value.chars().any(|c| c.is_ascii_digit());
+            is_bare_hex.then_some(value)
+        });
+    match hex {
+        Some(hex) => u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .map(CharacterQuery::Character)
+            .ok_or_else(|| format!("{value} is not a valid codepoint")),
+        None => Ok(CharacterQuery::Name(value.to_string())),
+    }
+}
+
+// The character as shown in lists, two columns wide: combining marks sit on a dotted circle and
+// invisible characters are left blank
+pub fn label(c: char) -> String {
+    match category(c) {
+        "Mn" | "Me" => format!("\u{25cc}{c} "),
+        "Cc" | "Cf" | "Zs" | "Zl" | "Zp" => "  ".to_string(),
+        _ if c.width() == Some(2) => c.to_string(),
+        _ => format!("{c} "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_characters_and_control_characters() {
+        assert_eq!(name('é').as_deref(), Some("LATIN SMALL LETTER E WITH ACUTE"));
+        assert_eq!(name('\u{200d}').as_deref(), Some("ZERO WIDTH JOINER"));
+        assert_eq!(name('\t').as_deref(), Some("CHARACTER TABULATION"));
+        assert_eq!(name('\u{7f}').as_deref(), Some("DELETE"));
+        assert_eq!(name('\u{85}').as_deref(), Some("NEXT LINE"));
+        assert_eq!(name('\u{9f}').as_deref(), Some("APPLICATION PROGRAM COMMAND"));
+        assert_eq!(name('\u{e000}'), None);
+    }
+
+    #[test]
+    fn describes_characters() {
+        assert_eq!(category('A'), "Lu");
+        assert_eq!(category('\u{301}'), "Mn");
+        assert_eq!(category('\u{85}'), "Cc");
+        assert_eq!(describe('é'), "U+00E9  Ll  C3 A9        LATIN SMALL LETTER E WITH ACUTE");
+        assert_eq!(describe('\u{85}'), "U+0085  Cc  C2 85        NEXT LINE");
+        assert_eq!(describe('\u{e000}'), "U+E000  Co  EE 80 80     (no name)");
+    }
+
+    fn parsed(value: &str) -> Result<String, String> {
+        parse_query(value).map(|query| match query {
+            CharacterQuery::Character(c) => c.to_string(),
+            CharacterQuery::Name(name) => format!("name {name}"),
+        })
+    }
+
+    #[test]
+    fn parses_digraphs_codepoints_and_names() {
+        assert_eq!(parsed("e'"), Ok("é".to_string()));
+        assert_eq!(parsed("->"), Ok("→".to_string()));
+        assert_eq!(parsed(" U+2192 "), Ok("→".to_string()));
+        assert_eq!(parsed("u+e9"), Ok("é".to_string()));
+        assert_eq!(parsed("0x2192"), Ok("→".to_string()));
+        assert_eq!(parsed("2192"), Ok("→".to_string()));
+        // bare hex without a digit is a word
+        assert_eq!(parsed("face"), Ok("name face".to_string()));
+        assert_eq!(parsed("rightwards arrow"), Ok("name rightwards arrow".to_string()));
+        // digraphs come before bare hex
+        assert_eq!(parsed("12"), Ok("½".to_string()));
+        assert_eq!(parsed("00"), Ok("∞".to_string()));
+        assert_eq!(parsed("U+12"), Ok("\u{12}".to_string()));
+    }
+
+    #[test]
+    fn rejects_invalid_codepoints() {
+        assert_eq!(parsed("U+D800"), Err("U+D800 is not a valid codepoint".to_string()));
+        assert_eq!(parsed("110000"), Err("110000 is not a valid codepoint".to_string()));
+        assert_eq!(parsed("0xZZ"), Err("0xZZ is not a valid codepoint".to_string()));
+        assert!(parsed("  ").is_err());
+    }
+
+    #[test]
+    fn finds_characters_by_name_and_digraph() {
+        assert_eq!(character(" rightwards arrow "), Some('→'));
+        assert_eq!(character("no such character"), None);
+        assert_eq!(digraph("a*"), Some('α'));
+        assert_eq!(digraph("zz"), None);
+        // the first of the digraphs for the same character
+        assert_eq!(digraph_for('Å'), Some("A0"));
+        assert_eq!(digraph_for('x'), None);
+    }
+
+    #[test]
+    fn lists_named_characters_with_their_digraphs() {
+        let characters = named_characters();
+        assert!(characters.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        let entry = |c| characters.iter().find(|(named, _)| *named == c).map(|(_, entry)| entry.as_str());
+        assert_eq!(entry('é'), Some("é   U+00E9   e'  LATIN SMALL LETTER E WITH ACUTE"));
+        assert_eq!(entry('\u{301}'), Some("\u{25cc}\u{301}   U+0301       COMBINING ACUTE ACCENT"));
+        // found by codepoint instead
+        assert_eq!(entry('\u{4e00}'), None);
+    }
+
+    #[test]
+    fn labels_take_two_columns() {
+        assert_eq!(label('a'), "a ");
+        assert_eq!(label('中'), "中");
+        assert_eq!(label('\u{301}'), "\u{25cc}\u{301} ");
+        assert_eq!(label('\u{85}'), "  ");
+    }
+}
@@ -1,19 +1,35 @@
 mod buffer;
 pub mod line;
 mod file_info;
+mod directory;
+mod gutter;
+mod indent;
+mod reflow;
+mod whitespace;
 
-use std::io::Error;
+use std::{ffi::OsStr, io::Error, ops::Range, path::Path};
+
+use regex::Regex;
 
 use super::{
     command::{edit::EditCommand, moves::MoveCommand}, 
     documentstatus::DocumentStatus, 
     position::{Location, Position}, 
     size::Size, terminal::Terminal, 
+    theme::Theme,
     uicomponent::UiComponent, 
     NAME, 
     VERSION
 };
 use buffer::Buffer;
+use line::{DisplayStyle, Line, Rendering};
+pub use directory::{DirectoryChange, DirectoryListing};
+pub use gutter::LineNumbers;
+pub use indent::IndentPolicy;
+pub use whitespace::TrimOnSave;
+use gutter::Gutter;
+use indent::IndentUnit;
+use file_info::FileInfo;
 
 #[derive(Default)]
 pub struct View {
@@ -23,8 +39,39 @@ pub struct View {
     text_location: Location,
     scroll_offset: Position,
     max_grapheme_ind: usize,
+    origin_col: usize,
+    recent_files: Vec<String>,
+    highlight: Option<(Location, Location)>,
+    soft_wrap: bool,
+    // with soft wrap, which of the screen rows of the top line is shown first
+    scroll_segment: usize,
+    // with soft wrap, the screen column moving up and down keeps returning to
+    sticky_col: Option<usize>,
+    wrap_column: usize,
+    // breaks the line at the wrap column while typing
+    auto_wrap: bool,
+    gutter: Gutter,
+    // the caret's line when the view was drawn, as relative line numbers count from it
+    drawn_caret_row: usize,
+    // tab stops, and whether tabs and other whitespace are drawn with glyphs
+    display_style: DisplayStyle,
+    indent_policy: IndentPolicy,
+    // what Tab inserts and new lines are indented by, as the policy says or as detected in the file
+    indent_unit: IndentUnit,
+    trim_on_save: TrimOnSave,
+    theme: Theme,
 }
 
+const MAX_WELCOME_RECENT_FILES: usize = 5;
+// starts the screen rows that continue a wrapped line
+const WRAP_MARKER: &str = "↪";
+const WRAP_MARKER_WIDTH: usize = 1;
+// ends each line while whitespace is shown
+const LINE_END_GLYPH: char = '¬';
+
+// A screen row of wrapped text, as the line and the index of its wrapped part
+type VisualRow = (usize, usize);
+
 impl View {
     pub fn get_status(&self) -> DocumentStatus {
         DocumentStatus {
@@ -32,6 +79,7 @@ impl View {
             curr_line_ind: self.text_location.row,
             is_modified: self.buffer.is_dirty(),
             file_name: format!("{}", self.buffer.file_info),
+            mode: None,
         }
     }
 
@@ -40,17 +88,224 @@ impl View {
     }
 
     pub fn load(&mut self, file_name: &str) -> Result<(), Error> {
-        self.buffer.load(file_name)?;
-        self.set_needs_redraw(true);
+        let mut buffer = Buffer::default();
+        buffer.load(file_name)?;
+        self.replace_buffer(buffer);
         Ok(())
     }
 
+    // Shows the directory as an editable listing, one entry per line
+    pub fn load_directory(&mut self, path: &str) -> Result<(), Error> {
+        let mut buffer = Buffer::default();
+        buffer.load_directory(path)?;
+        self.replace_buffer(buffer);
+        Ok(())
+    }
+
+    fn replace_buffer(&mut self, buffer: Buffer) {
+        self.buffer = buffer;
+        self.text_location = Location::default();
+        self.scroll_offset = Position::default();
+        self.scroll_segment = 0;
+        self.max_grapheme_ind = 0;
+        self.resolve_indent_unit();
+        self.set_needs_redraw(true);
+    }
+
+    // The changes on disk the edited directory listing stands for, if a directory is being edited
+    pub fn directory_changes(&self) -> Option<Result<Vec<DirectoryChange>, String>> {
+        self.buffer.directory_changes()
+    }
+
+    pub fn directory_root(&self) -> Option<&Path> {
+        self.buffer.directory.as_ref().map(DirectoryListing::root)
+    }
+
     pub fn save_as(&mut self, file_name: &str) -> Result<(), Error> {
-        self.buffer.save_as(file_name)
+        let result = self.buffer.save_as(file_name, self.trim_on_save);
+        self.after_whitespace_change();
+        result
+    }
+
+    pub fn file_path(&self) -> Option<&Path> {
+        self.buffer.file_info.get_path()
+    }
+
+    // Points the buffer at a new path without writing it, e.g. after the file got renamed on disk
+    pub fn set_file_path(&mut self, file_name: &str) {
+        self.buffer.file_info = FileInfo::from(file_name);
+    }
+
+    // Forgets the file the buffer was loaded from, e.g. after it got deleted on disk, so that saving asks for a
+    // name instead of creating it again
+    pub fn detach_file(&mut self) {
+        self.buffer.file_info = FileInfo::new();
+        self.buffer.dirty = true;
+        self.set_needs_redraw(true);
+    }
+
+    // The screen column the view starts at, leaving room for panels to its left
+    pub fn set_origin_col(&mut self, col: usize) {
+        self.origin_col = col;
+        self.set_needs_redraw(true);
+    }
+
+    // The files listed below the welcome message
+    pub fn set_recent_files(&mut self, recent_files: Vec<String>) {
+        self.recent_files = recent_files;
+        self.set_needs_redraw(true);
+    }
+
+    pub const fn location(&self) -> Location {
+        self.text_location
+    }
+
+    // Moves the caret to the location, or as close to it as the buffer allows
+    pub fn set_location(&mut self, location: Location) {
+        self.sticky_col = None;
+        self.text_location.row = location.row;
+        self.snap_to_valid_line();
+        self.max_grapheme_ind = location.grapheme_index;
+        self.snap_to_valid_grapheme();
+        self.scroll_into_view();
+    }
+
+    // Displays long lines across several screen rows instead of scrolling horizontally
+    pub fn set_soft_wrap(&mut self, soft_wrap: bool) {
+        if soft_wrap != self.soft_wrap {
+            self.soft_wrap = soft_wrap;
+            self.scroll_offset.col = 0;
+            self.scroll_segment = 0;
+            self.set_needs_redraw(true);
+            self.scroll_into_view();
+        }
+    }
+
+    pub fn set_line_numbers(&mut self, line_numbers: LineNumbers) {
+        self.gutter.set_line_numbers(line_numbers);
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    // The width of the gutter left of the text, which is hidden while the buffer is empty
+    fn gutter_width(&self) -> usize {
+        if self.buffer.is_empty() {
+            0
+        } else {
+            self.gutter.width(self.buffer.height())
+        }
+    }
+
+    fn gutter_text(&self, row: Option<usize>) -> String {
+        if self.buffer.is_empty() {
+            return String::new();
+        }
+        self.gutter.render(row, self.text_location.row, self.buffer.height())
+    }
+
+    // How many columns are left for the text next to the gutter
+    fn text_width(&self) -> usize {
+        self.size.width.saturating_sub(self.gutter_width())
+    }
+
+    // Puts tab stops every `tab_width` columns, and shows tabs with a glyph or as blank space
+    pub fn set_tab_display(&mut self, tab_width: usize, show_tabs: bool) {
+        self.display_style.tab_width = tab_width.max(1);
+        self.display_style.show_tabs = show_tabs;
+        self.resolve_indent_unit();
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    pub fn set_indent_policy(&mut self, policy: IndentPolicy) {
+        self.indent_policy = policy;
+        self.resolve_indent_unit();
+    }
+
+    fn resolve_indent_unit(&mut self) {
+        self.indent_unit = IndentUnit::resolve(self.indent_policy, &self.buffer.lines, self.display_style.tab_width);
+    }
+
+    fn file_extension(&self) -> Option<&str> {
+        self.buffer.file_info.get_path()?.extension().and_then(OsStr::to_str)
+    }
+
+    // Shows spaces, tabs, line ends and control characters, and flags trailing whitespace
+    pub fn set_show_whitespace(&mut self, show_whitespace: bool) {
+        if show_whitespace != self.display_style.show_whitespace {
+            self.display_style.show_whitespace = show_whitespace;
+            self.set_needs_redraw(true);
+            self.scroll_into_view();
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.set_needs_redraw(true);
+    }
+
+    pub fn set_trim_on_save(&mut self, trim_on_save: TrimOnSave) {
+        self.trim_on_save = trim_on_save;
+    }
+
+    pub fn set_wrap_column(&mut self, wrap_column: usize, auto_wrap: bool) {
+        self.wrap_column = wrap_column;
+        self.auto_wrap = auto_wrap;
+    }
+
+    // Shows the text from `start` up to `end` inverted, e.g. to mark a selection
+    pub fn set_highlight(&mut self, highlight: Option<(Location, Location)>) {
+        if highlight != self.highlight {
+            self.highlight = highlight;
+            self.set_needs_redraw(true);
+        }
+    }
+
+    // The visible columns of the row, with the highlighted part inverted. While whitespace is shown,
+    // trailing whitespace is flagged and the end of the line marked.
+    fn styled_line(&self, row: usize, columns: Range<usize>) -> Option<String> {
+        let line = self.buffer.line(row)?;
+        let style = self.display_style;
+        let clamp = |col: usize| col.clamp(columns.start, columns.end);
+
+        let highlighted = self.highlight.filter(|(start, end)| (start.row..=end.row).contains(&row)).map(|(start, end)| {
+            let from = if row == start.row { start.grapheme_index } else { 0 };
+            let to = if row == end.row { end.grapheme_index } else { line.grapheme_count() };
+            clamp(line.width_until(from, style))..clamp(line.width_until(to, style))
+        });
+        let show_whitespace = style.show_whitespace;
+        let trailing = if show_whitespace {
+            clamp(line.width_until(line.trailing_whitespace_start(), style))..clamp(line.width(style))
+        } else {
+            columns.end..columns.end
+        };
+
+        let mut bounds = vec![columns.start, columns.end, trailing.start, trailing.end];
+        bounds.extend(highlighted.iter().flat_map(|range| [range.start, range.end]));
+        bounds.sort_unstable();
+        bounds.dedup();
+
+        let mut text: String = bounds
+            .windows(2)
+            .map(|part| {
+                let part_text = line.get(part[0]..part[1], style);
+                if highlighted.as_ref().is_some_and(|range| range.contains(&part[0])) {
+                    self.theme.highlighted(&part_text)
+                } else if trailing.contains(&part[0]) {
+                    self.theme.flagged(&part_text)
+                } else {
+                    part_text
+                }
+            })
+            .collect();
+        if show_whitespace && (columns.start..=columns.end).contains(&line.width(style)) {
+            text.push(LINE_END_GLYPH);
+        }
+        Some(text)
     }
 
-    fn render_text(row: usize, text: &str) {
-        let result = Terminal::print_row(row, text);
+    fn render_text(&self, row: usize, text: &str) {
+        let result = Terminal::print_row_from(Position { row, col: self.origin_col }, text);
         debug_assert!(result.is_ok(), "Error rendering text on line");
     }
 
@@ -71,15 +326,56 @@ impl View {
         format!("{:<1}{:^remaining_width$}", "~", welcome_message)
     }
 
+    fn generate_recent_files_message(&self, width: usize) -> Vec<String> {
+        if self.recent_files.is_empty() {
+            return Vec::new();
+        }
+
+        let remaining_width = width.saturating_sub(1);
+        let heading = "Recent files (Ctrl-R to pick one):".to_string();
+        let files = self
+            .recent_files
+            .iter()
+            .take(MAX_WELCOME_RECENT_FILES)
+            .cloned();
+
+        [String::new(), heading]
+            .into_iter()
+            .chain(files)
+            .map(|text| {
+                // like the welcome message, entries that don't fit are hidden
+                if remaining_width < text.len() {
+                    "~".to_string()
+                } else {
+                    format!("{:<1}{:^remaining_width$}", "~", text)
+                }
+            })
+            .collect()
+    }
+
     pub fn caret_position(&self) -> Position {
-        self.text_location_to_position()
-            .saturating_sub(&self.scroll_offset)
+        let Position { row, col } = if self.soft_wrap {
+            self.wrapped_caret_position()
+        } else {
+            self.text_location_to_position().saturating_sub(&self.scroll_offset)
+        };
+        Position { row, col: col.saturating_add(self.origin_col).saturating_add(self.gutter_width()) }
     }
 
     pub fn handle_move_command(&mut self, command: MoveCommand) {
         let Size { height, .. } = self.size;
+        if !matches!(
+            command,
+            MoveCommand::Up | MoveCommand::Down | MoveCommand::PageUp | MoveCommand::PageDown
+        ) {
+            self.sticky_col = None;
+        }
 
         match command {
+            MoveCommand::Up if self.soft_wrap => self.move_visually(1, false),
+            MoveCommand::Down if self.soft_wrap => self.move_visually(1, true),
+            MoveCommand::PageUp if self.soft_wrap => self.move_visually(height.saturating_sub(1), false),
+            MoveCommand::PageDown if self.soft_wrap => self.move_visually(height.saturating_sub(1), true),
             MoveCommand::Up => self.move_up(1),
             MoveCommand::Down => self.move_down(1),
             MoveCommand::Left => self.move_left(),
@@ -88,35 +384,457 @@ impl View {
             MoveCommand::PageDown => self.move_down(height.saturating_sub(1)),
             MoveCommand::Home => self.home_action(),
             MoveCommand::End => self.end_action(),
+            MoveCommand::WordForward => self.move_word_right(),
+            MoveCommand::WordBackward => self.move_word_left(),
+            MoveCommand::ParagraphUp => self.move_paragraph_up(),
+            MoveCommand::ParagraphDown => self.move_paragraph_down(),
         }
 
         self.scroll_into_view();
     }
 
     pub fn handle_edit_command(&mut self, command: EditCommand) {
+        self.sticky_col = None;
         match command {
-            EditCommand::Insert(c) => self.insert_char(c),
+            EditCommand::Insert('\t') => self.insert_tab(),
+            EditCommand::Insert(c) => self.type_char(c),
             EditCommand::InsertNewline => self.perform_newline(),
             EditCommand::Delete => self.perform_backspace(),
             EditCommand::DeleteBackward => self.perform_delete(),
+            EditCommand::DeleteWordBackward => self.perform_delete_word_backward(),
+            EditCommand::DeleteWordForward => self.perform_delete_word_forward(),
+            EditCommand::Reflow => self.reflow(),
+            EditCommand::Indent => self.indent_lines(),
+            EditCommand::Dedent => self.dedent_lines(),
         }
     }
 
+    // Redoes the indentation of the rows with tabs or spaces, as the indentation style says unless given.
+    // Returns how many lines changed.
+    pub fn retab(&mut self, rows: Range<usize>, to_tabs: Option<bool>) -> usize {
+        let to_tabs = to_tabs.unwrap_or(self.indent_unit == IndentUnit::Tab);
+        let count = self.buffer.retab(rows, self.display_style.tab_width, to_tabs);
+        self.resolve_indent_unit();
+        self.after_whitespace_change();
+        count
+    }
+
+    pub fn trim_trailing_whitespace(&mut self, rows: Range<usize>, only_modified: bool) -> usize {
+        let count = self.buffer.trim_trailing_whitespace(rows, only_modified);
+        self.after_whitespace_change();
+        count
+    }
+
+    pub fn squeeze_blank_lines(&mut self, rows: Range<usize>) -> usize {
+        let count = self.buffer.squeeze_blank_lines(rows);
+        self.after_whitespace_change();
+        count
+    }
+
+    // Keeps the caret on the text after whitespace was removed from around it
+    fn after_whitespace_change(&mut self) {
+        self.snap_to_valid_line();
+        self.max_grapheme_ind = self.text_location.grapheme_index;
+        self.snap_to_valid_grapheme();
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    // Replaces the pattern in the rows, returning how many occurrences got replaced
+    pub fn substitute(&mut self, rows: Range<usize>, pattern: &Regex, replacement: &str, global: bool) -> usize {
+        let count = self.buffer.substitute(rows, pattern, replacement, global);
+        if count > 0 {
+            self.snap_to_valid_grapheme();
+            self.set_needs_redraw(true);
+            self.scroll_into_view();
+        }
+        count
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.buffer.height()
+    }
+
+    pub fn grapheme_count(&self, row: usize) -> usize {
+        self.buffer.grapheme_count(row)
+    }
+
+    pub fn first_non_blank(&self, row: usize) -> usize {
+        self.buffer.line(row).map_or(0, Line::first_non_blank)
+    }
+
+    // Where the next word starts, continuing on the following line if there is none on this one
+    pub fn word_forward(&self, from: Location) -> Location {
+        let Location { row, grapheme_index } = from;
+        if let Some(start) = self.buffer.line(row).and_then(|line| line.word_start_from(grapheme_index + 1)) {
+            return Location { row, grapheme_index: start };
+        }
+
+        if row + 1 < self.buffer.height() {
+            let start = self.buffer.line(row + 1).and_then(|line| line.word_start_from(0)).unwrap_or(0);
+            Location { row: row + 1, grapheme_index: start }
+        } else {
+            Location { row, grapheme_index: self.buffer.grapheme_count(row) }
+        }
+    }
+
+    // Where the word before the location starts, continuing on the previous line if there is none on this one
+    pub fn word_backward(&self, from: Location) -> Location {
+        let Location { row, grapheme_index } = from;
+        if let Some(start) = self.buffer.line(row).and_then(|line| line.word_start_before(grapheme_index)) {
+            return Location { row, grapheme_index: start };
+        }
+
+        match row.checked_sub(1) {
+            Some(row) => {
+                let start = self.buffer.line(row).and_then(|line| line.word_start_before(usize::MAX)).unwrap_or(0);
+                Location { row, grapheme_index: start }
+            }
+            None => Location::default(),
+        }
+    }
+
+    // The last grapheme of the word ending after the location, skipping lines without words
+    pub fn word_end(&self, from: Location) -> Location {
+        let Location { row, grapheme_index } = from;
+        if let Some(end) = self.buffer.line(row).and_then(|line| line.word_end_from(grapheme_index + 1)) {
+            return Location { row, grapheme_index: end };
+        }
+
+        (row + 1..self.buffer.height())
+            .find_map(|row| {
+                let end = self.buffer.line(row)?.word_end_from(0)?;
+                Some(Location { row, grapheme_index: end })
+            })
+            .unwrap_or(from)
+    }
+
+    // The word, or run of whitespace or punctuation, the location is in
+    pub fn segment_at(&self, at: Location) -> (Location, Location) {
+        let span = self.buffer.line(at.row).map_or(0..0, |line| line.segment_at(at.grapheme_index));
+        (
+            Location { row: at.row, grapheme_index: span.start },
+            Location { row: at.row, grapheme_index: span.end },
+        )
+    }
+
+    fn graphemes_before(&self, at: Location) -> impl Iterator<Item = (Location, &str)> {
+        (0..=at.row).rev().flat_map(move |row| {
+            let line = self.buffer.line(row);
+            let end = if row == at.row { at.grapheme_index + 1 } else { usize::MAX };
+            let end = end.min(line.map_or(0, Line::grapheme_count));
+            (0..end).rev().filter_map(move |grapheme_index| {
+                let grapheme = line?.grapheme_at(grapheme_index)?;
+                Some((Location { grapheme_index, row }, grapheme))
+            })
+        })
+    }
+
+    fn graphemes_after(&self, at: Location) -> impl Iterator<Item = (Location, &str)> {
+        (at.row..self.buffer.height()).flat_map(move |row| {
+            let line = self.buffer.line(row);
+            let start = if row == at.row { at.grapheme_index + 1 } else { 0 };
+            let end = line.map_or(0, Line::grapheme_count);
+            (start..end).filter_map(move |grapheme_index| {
+                let grapheme = line?.grapheme_at(grapheme_index)?;
+                Some((Location { grapheme_index, row }, grapheme))
+            })
+        })
+    }
+
+    // The locations of the `open` and `close` pair around the location, which may span several lines
+    pub fn enclosing_pair(&self, at: Location, open: &str, close: &str) -> Option<(Location, Location)> {
+        let mut depth = 0usize;
+        let open_at = self.graphemes_before(at).find_map(|(location, grapheme)| {
+            if grapheme == close && location != at {
+                depth += 1;
+            } else if grapheme == open {
+                if depth == 0 {
+                    return Some(location);
+                }
+                depth -= 1;
+            }
+            None
+        })?;
+
+        let mut depth = 0usize;
+        let close_at = self.graphemes_after(open_at).find_map(|(location, grapheme)| {
+            if grapheme == open {
+                depth += 1;
+            } else if grapheme == close {
+                if depth == 0 {
+                    return Some(location);
+                }
+                depth -= 1;
+            }
+            None
+        })?;
+
+        Some((open_at, close_at))
+    }
+
+    // The locations of the `quote` pair around the location, within its line
+    pub fn enclosing_quotes(&self, at: Location, quote: &str) -> Option<(Location, Location)> {
+        let line = self.buffer.line(at.row)?;
+        let quotes: Vec<usize> = (0..line.grapheme_count())
+            .filter(|ind| line.grapheme_at(*ind) == Some(quote))
+            .collect();
+        let pair = quotes
+            .chunks_exact(2)
+            .find(|pair| pair[0] <= at.grapheme_index && at.grapheme_index <= pair[1])?;
+
+        Some((
+            Location { row: at.row, grapheme_index: pair[0] },
+            Location { row: at.row, grapheme_index: pair[1] },
+        ))
+    }
+
+    pub fn text_between(&self, start: Location, end: Location) -> String {
+        self.buffer.text_between(start, end)
+    }
+
+    // The grapheme under the caret, the byte offset it starts at in the saved file and how it is drawn
+    pub fn grapheme_under_caret(&self) -> Option<(String, usize, Rendering)> {
+        let Location { row, grapheme_index } = self.text_location;
+        let line = self.buffer.line(row)?;
+        let grapheme = line.grapheme_at(grapheme_index)?.to_string();
+        let offset = self.buffer.text_between(Location::default(), self.text_location).len();
+        Some((grapheme, offset, line.rendering_at(grapheme_index, self.display_style)?))
+    }
+
+    // The whole lines in the range, each ending with a newline
+    pub fn lines_text(&self, rows: Range<usize>) -> String {
+        self.buffer.lines_text(rows)
+    }
+
+    // Removes the text from `start` up to `end`, leaving the caret where it started
+    pub fn delete_between(&mut self, start: Location, end: Location) {
+        self.buffer.delete_between(start, end);
+        self.set_location(start);
+        self.set_needs_redraw(true);
+    }
+
+    pub fn delete_lines(&mut self, rows: Range<usize>) {
+        let row = rows.start;
+        self.buffer.delete_lines(rows);
+        let row = row.min(self.buffer.height().saturating_sub(1));
+        self.set_location(Location { row, grapheme_index: self.first_non_blank(row) });
+        self.set_needs_redraw(true);
+    }
+
+    // Inserts text that may span several lines at the location, leaving the caret right after it
+    pub fn insert_text(&mut self, at: Location, text: &str) {
+        let end = self.buffer.insert_text(at, text);
+        self.set_location(end);
+        self.set_needs_redraw(true);
+    }
+
     pub fn save(&mut self) -> Result<(), Error> {
-        self.buffer.save()
+        let result = self.buffer.save(self.trim_on_save);
+        self.after_whitespace_change();
+        result
+    }
+
+    // Scrolls so the caret's line is in the middle of the view
+    pub fn center_caret(&mut self) {
+        if self.soft_wrap {
+            let mut top = self.caret_visual_row();
+            for _ in 0..self.size.height / 2 {
+                let Some(previous) = self.previous_visual_row(top) else {
+                    break;
+                };
+                top = previous;
+            }
+            (self.scroll_offset.row, self.scroll_segment) = top;
+            self.set_needs_redraw(true);
+            return;
+        }
+
+        let Position { row, col } = self.text_location_to_position();
+        let s_row = row.saturating_sub(self.size.height / 2);
+        if s_row != self.scroll_offset.row {
+            self.scroll_offset.row = s_row;
+            self.set_needs_redraw(true);
+        }
+        self.scroll_horizontally(col);
     }
 
     fn scroll_into_view(&mut self) {
+        if self.gutter.follows_caret() && self.text_location.row != self.drawn_caret_row {
+            self.set_needs_redraw(true);
+        }
+        if self.soft_wrap {
+            self.scroll_wrapped();
+            return;
+        }
+
         let Position { row, col } = self.text_location_to_position();
         self.scroll_vertically(row);
         self.scroll_horizontally(col);
     }
 
+    // The grapheme ranges of the line that each take a screen row when wrapped
+    fn wrapped_segments(&self, row: usize) -> Vec<Range<usize>> {
+        let width = self.text_width();
+        self.buffer
+            .line(row)
+            .map_or_else(|| Line::default().wrap(width, width, self.display_style), |line| {
+                line.wrap(width, width.saturating_sub(WRAP_MARKER_WIDTH), self.display_style)
+            })
+    }
+
+    // Which wrapped part of its line the grapheme index is shown in
+    fn segment_index(segments: &[Range<usize>], grapheme_index: usize) -> usize {
+        segments
+            .iter()
+            .position(|segment| grapheme_index < segment.end)
+            .unwrap_or(segments.len().saturating_sub(1))
+    }
+
+    fn caret_visual_row(&self) -> VisualRow {
+        let Location { row, grapheme_index } = self.text_location;
+        (row, Self::segment_index(&self.wrapped_segments(row), grapheme_index))
+    }
+
+    fn previous_visual_row(&self, (row, segment): VisualRow) -> Option<VisualRow> {
+        if segment > 0 {
+            Some((row, segment - 1))
+        } else if row > 0 {
+            Some((row - 1, self.wrapped_segments(row - 1).len().saturating_sub(1)))
+        } else {
+            None
+        }
+    }
+
+    fn next_visual_row(&self, (row, segment): VisualRow) -> Option<VisualRow> {
+        if segment + 1 < self.wrapped_segments(row).len() {
+            Some((row, segment + 1))
+        } else if row < self.buffer.height() {
+            Some((row + 1, 0))
+        } else {
+            None
+        }
+    }
+
+    // The column of the grapheme on its screen row, including the continuation marker
+    fn visual_col(&self, row: usize, segment: &Range<usize>, segment_ind: usize, grapheme_index: usize) -> usize {
+        let marker = if segment_ind > 0 { WRAP_MARKER_WIDTH } else { 0 };
+        let width_from_start = self.buffer.row_width_until(row, grapheme_index, self.display_style)
+            .saturating_sub(self.buffer.row_width_until(row, segment.start, self.display_style));
+        marker + width_from_start
+    }
+
+    // Scrolls by screen rows so the caret's wrapped row is visible
+    fn scroll_wrapped(&mut self) {
+        let caret = self.caret_visual_row();
+        let top_segments = self.wrapped_segments(self.scroll_offset.row).len();
+        let top = (self.scroll_offset.row, self.scroll_segment.min(top_segments.saturating_sub(1)));
+
+        // the top row that shows the caret on the bottom row
+        let mut lowest_top = caret;
+        for _ in 1..self.size.height {
+            let Some(previous) = self.previous_visual_row(lowest_top) else {
+                break;
+            };
+            lowest_top = previous;
+        }
+
+        let new_top = if caret < top {
+            caret
+        } else if top < lowest_top {
+            lowest_top
+        } else {
+            top
+        };
+        if new_top != (self.scroll_offset.row, self.scroll_segment) || self.scroll_offset.col != 0 {
+            (self.scroll_offset.row, self.scroll_segment) = new_top;
+            self.scroll_offset.col = 0;
+            self.set_needs_redraw(true);
+        }
+    }
+
+    fn wrapped_caret_position(&self) -> Position {
+        let caret = self.caret_visual_row();
+        let mut visual_row = (self.scroll_offset.row, self.scroll_segment);
+        let mut row = 0;
+        while visual_row < caret && row < self.size.height {
+            let Some(next) = self.next_visual_row(visual_row) else {
+                break;
+            };
+            visual_row = next;
+            row += 1;
+        }
+
+        let segments = self.wrapped_segments(caret.0);
+        let col = self.visual_col(caret.0, &segments[caret.1], caret.1, self.text_location.grapheme_index);
+        Position { row, col: col.min(self.text_width().saturating_sub(1)) }
+    }
+
+    // Moves the caret up or down by screen rows, keeping its column where the row is long enough
+    fn move_visually(&mut self, steps: usize, down: bool) {
+        let (row, segment_ind) = self.caret_visual_row();
+        let segments = self.wrapped_segments(row);
+        let col = self.sticky_col.unwrap_or_else(|| {
+            self.visual_col(row, &segments[segment_ind], segment_ind, self.text_location.grapheme_index)
+        });
+
+        let mut target = (row, segment_ind);
+        for _ in 0..steps {
+            let next = if down { self.next_visual_row(target) } else { self.previous_visual_row(target) };
+            match next {
+                Some(next) => target = next,
+                None => break,
+            }
+        }
+
+        let (row, segment_ind) = target;
+        let segments = self.wrapped_segments(row);
+        let segment = segments[segment_ind].clone();
+        let is_last = segment_ind + 1 == segments.len();
+        let grapheme_index = segment
+            .clone()
+            .find(|&ind| self.visual_col(row, &segment, segment_ind, ind + 1) > col)
+            .unwrap_or(if is_last { segment.end } else { segment.end.saturating_sub(1) });
+
+        self.text_location = Location { grapheme_index, row };
+        self.max_grapheme_ind = grapheme_index;
+        self.sticky_col = Some(col);
+    }
+
+    // The text of each screen row from the top of the view, wrapping long lines
+    fn wrapped_screen_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut visual_row = Some((self.scroll_offset.row, self.scroll_segment));
+
+        while let Some((row, segment_ind)) = visual_row.filter(|(row, _)| *row < self.buffer.height()) {
+            if lines.len() >= self.size.height {
+                break;
+            }
+            let Some(line) = self.buffer.line(row) else {
+                break;
+            };
+            let segments = self.wrapped_segments(row);
+            let segment = &segments[segment_ind.min(segments.len() - 1)];
+            let columns =
+                line.width_until(segment.start, self.display_style)..line.width_until(segment.end, self.display_style);
+            let text = self.styled_line(row, columns).unwrap_or_default();
+            let (gutter, marker) = if segment_ind > 0 {
+                (self.gutter_text(None), WRAP_MARKER)
+            } else {
+                (self.gutter_text(Some(row)), "")
+            };
+            lines.push(format!("{gutter}{marker}{text}"));
+
+            visual_row = self.next_visual_row((row, segment_ind));
+        }
+        lines
+    }
+
     fn text_location_to_position(&self) -> Position {
         let row = self.text_location.row;
         let col = self
             .buffer
-            .row_width_until(row, self.text_location.grapheme_index);
+            .row_width_until(row, self.text_location.grapheme_index, self.display_style);
         Position { row, col }
     }
 
@@ -138,7 +856,7 @@ impl View {
     }
 
     fn scroll_horizontally(&mut self, col: usize) {
-        let width = self.size.width;
+        let width = self.text_width();
         let mut s_col = self.scroll_offset.col;
         let mut offset_changed = false;
 
@@ -196,6 +914,71 @@ impl View {
         self.max_grapheme_ind = self.text_location.grapheme_index;
     }
 
+    // Moves to the start of the word before the caret, or of the line if there is none.
+    // At the start of a line it moves to the end of the previous one, like `move_left`.
+    fn move_word_left(&mut self) {
+        let Location { row, grapheme_index } = self.text_location;
+        if grapheme_index == 0 {
+            self.move_left();
+            return;
+        }
+
+        let start = self.buffer.previous_word_start(row, grapheme_index);
+        self.text_location.grapheme_index = start;
+        self.max_grapheme_ind = start;
+    }
+
+    // Moves past the end of the word the caret is in or before, or to the end of the line if there is none.
+    // At the end of a line it moves to the start of the next one, like `move_right`.
+    fn move_word_right(&mut self) {
+        let Location { row, grapheme_index } = self.text_location;
+        let line_length = self.buffer.grapheme_count(row);
+        if grapheme_index >= line_length {
+            self.move_right();
+            return;
+        }
+
+        let end = self.buffer.next_word_end(row, grapheme_index);
+        self.text_location.grapheme_index = end;
+        self.max_grapheme_ind = end;
+    }
+
+    fn is_blank_line(&self, row: usize) -> bool {
+        self.buffer.line(row).is_none_or(|line| line.first_non_blank() == line.grapheme_count())
+    }
+
+    // Moves to the blank line before the paragraph, or to the first line if there is none
+    fn move_paragraph_up(&mut self) {
+        let mut row = self.text_location.row.min(self.buffer.height().saturating_sub(1));
+        while row > 0 && self.is_blank_line(row) {
+            row -= 1;
+        }
+        while row > 0 && !self.is_blank_line(row) {
+            row -= 1;
+        }
+        self.set_location(Location { grapheme_index: 0, row });
+    }
+
+    // Moves to the blank line after the paragraph, or to the end of the last line if there is none
+    fn move_paragraph_down(&mut self) {
+        let height = self.buffer.height();
+        let mut row = self.text_location.row;
+        while row < height && self.is_blank_line(row) {
+            row += 1;
+        }
+        while row < height && !self.is_blank_line(row) {
+            row += 1;
+        }
+
+        let location = if row < height {
+            Location { grapheme_index: 0, row }
+        } else {
+            let row = height.saturating_sub(1);
+            Location { grapheme_index: self.buffer.grapheme_count(row), row }
+        };
+        self.set_location(location);
+    }
+
     fn home_action(&mut self) {
         self.move_to_start_of_line();
         self.max_grapheme_ind = self.text_location.grapheme_index;
@@ -211,7 +994,7 @@ impl View {
     }
 
     fn move_to_end_of_line(&mut self) {
-        self.text_location.grapheme_index = self.buffer.grapheme_count(self.text_location.row)
+        self.text_location.grapheme_index = self.buffer.grapheme_count(self.text_location.row);
     }
 
     fn snap_to_valid_line(&mut self) {
@@ -232,10 +1015,158 @@ impl View {
         if has_len_increased {
             self.move_right();
         }
+        if self.auto_wrap && !c.is_whitespace() {
+            self.wrap_at_column();
+        }
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    // Moves the words past the wrap column onto a new line, which repeats the indentation and comment marker
+    fn wrap_at_column(&mut self) {
+        let Location { row, grapheme_index } = self.text_location;
+        let Some(line) = self.buffer.line(row) else {
+            return;
+        };
+        if line.width_until(grapheme_index, self.display_style) <= self.wrap_column {
+            return;
+        }
+
+        let text = line.as_string();
+        let caret = line.get_graphemes(0..grapheme_index).len();
+        let Some(gap) = reflow::break_point(&text, caret, self.wrap_column, self.display_style) else {
+            return;
+        };
+        let prefix = reflow::split_prefix(&text).0;
+        let moved = Line::from(&text[gap.end..caret]).grapheme_count();
+
+        self.buffer.replace_lines(row..row + 1, &[text[..gap.start].to_string(), format!("{prefix}{}", &text[gap.end..])]);
+        self.text_location.row = row + 1;
+        self.max_grapheme_ind = Line::from(prefix).grapheme_count() + moved;
+        self.snap_to_valid_grapheme();
+    }
+
+    // Fills the selected lines, or the paragraph around the caret, up to the wrap column
+    fn reflow(&mut self) {
+        let rows = self.selected_rows().unwrap_or_else(|| self.paragraph_rows(self.text_location.row));
+        let rows = rows.start..rows.end.min(self.buffer.height());
+        if rows.is_empty() {
+            return;
+        }
+
+        let lines: Vec<String> = rows.clone().filter_map(|row| self.buffer.line(row).map(Line::as_string)).collect();
+        let reflowed = reflow::reflow(&lines, self.wrap_column, self.display_style);
+        if reflowed != lines {
+            self.buffer.replace_lines(rows.clone(), &reflowed);
+        }
+
+        self.text_location.row = (rows.start + reflowed.len()).saturating_sub(1);
+        self.max_grapheme_ind = self.buffer.grapheme_count(self.text_location.row);
+        self.snap_to_valid_grapheme();
         self.set_needs_redraw(true);
         self.scroll_into_view();
     }
 
+    // The rows the highlight covers, if there is one
+    fn selected_rows(&self) -> Option<Range<usize>> {
+        let (start, end) = self.highlight?;
+        // a selection ending at the start of a line doesn't include that line
+        if end.grapheme_index == 0 && end.row > start.row {
+            Some(start.row..end.row)
+        } else {
+            Some(start.row..end.row + 1)
+        }
+    }
+
+    // Types the character, taking back one level of indentation when it closes a block at the line start
+    fn type_char(&mut self, c: char) {
+        let Location { row, grapheme_index } = self.text_location;
+        let before = self.buffer.line(row).map(|line| line.get_graphemes(0..grapheme_index)).unwrap_or_default();
+        if !before.is_empty() && before.trim().is_empty() && indent::is_block_closer(c, self.file_extension()) {
+            let len = indent::dedent_len(&before, self.indent_unit, self.display_style.tab_width);
+            self.buffer.delete_range(row, 0..len);
+            self.text_location.grapheme_index -= len;
+            self.max_grapheme_ind = self.text_location.grapheme_index;
+        }
+        self.insert_char(c);
+    }
+
+    // Indents the selected lines, or inserts one level of indentation at the caret
+    fn insert_tab(&mut self) {
+        if self.highlight.is_some() {
+            self.indent_lines();
+            return;
+        }
+        match self.indent_unit {
+            IndentUnit::Tab => self.insert_char('\t'),
+            IndentUnit::Spaces(width) => {
+                // up to the next multiple of the width
+                let Location { row, grapheme_index } = self.text_location;
+                let col = self.buffer.row_width_until(row, grapheme_index, self.display_style);
+                for _ in 0..width - col % width {
+                    self.insert_char(' ');
+                }
+            }
+        }
+    }
+
+    // Indents the selected lines, or the caret's line, by one level. Blank lines stay empty.
+    fn indent_lines(&mut self) {
+        let caret_row = self.text_location.row;
+        let rows = self.selected_rows().unwrap_or(caret_row..caret_row + 1);
+        let unit = self.indent_unit.text();
+
+        for row in rows.start..rows.end.min(self.buffer.height()) {
+            if self.buffer.line(row).is_some_and(|line| !line.as_string().trim().is_empty()) {
+                self.buffer.insert_text(Location { row, grapheme_index: 0 }, &unit);
+                if row == caret_row {
+                    self.text_location.grapheme_index += Line::from(unit.as_str()).grapheme_count();
+                }
+            }
+        }
+        self.max_grapheme_ind = self.text_location.grapheme_index;
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    // Takes one level of indentation from the selected lines, or the caret's line
+    fn dedent_lines(&mut self) {
+        let caret_row = self.text_location.row;
+        let rows = self.selected_rows().unwrap_or(caret_row..caret_row + 1);
+
+        for row in rows.start..rows.end.min(self.buffer.height()) {
+            let text = self.buffer.line(row).map(Line::as_string).unwrap_or_default();
+            let len = indent::dedent_len(&text, self.indent_unit, self.display_style.tab_width);
+            if len > 0 {
+                self.buffer.delete_range(row, 0..len);
+                if row == caret_row {
+                    self.text_location.grapheme_index = self.text_location.grapheme_index.saturating_sub(len);
+                }
+            }
+        }
+        self.max_grapheme_ind = self.text_location.grapheme_index;
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    // The lines of the paragraph around the row, which share its comment marker; none on a blank line
+    fn paragraph_rows(&self, row: usize) -> Range<usize> {
+        let text = |row: usize| self.buffer.line(row).map(Line::as_string).unwrap_or_default();
+        if !reflow::has_text(&text(row)) {
+            return row..row;
+        }
+
+        let mut start = row;
+        while start > 0 && reflow::same_paragraph(&text(start - 1), &text(start)) {
+            start -= 1;
+        }
+        let mut end = row + 1;
+        while end < self.buffer.height() && reflow::same_paragraph(&text(end - 1), &text(end)) {
+            end += 1;
+        }
+        start..end
+    }
+
     fn perform_backspace(&mut self) {
         let Location { row, grapheme_index } = self.text_location;
 
@@ -268,8 +1199,64 @@ impl View {
         self.scroll_into_view();
     }
 
+    fn perform_delete_word_backward(&mut self) {
+        let Location { row, grapheme_index } = self.text_location;
+        if grapheme_index == 0 {
+            self.perform_backspace();
+            return;
+        }
+
+        let start = self.buffer.previous_word_start(row, grapheme_index);
+        self.buffer.delete_range(row, start..grapheme_index);
+        self.text_location.grapheme_index = start;
+        self.max_grapheme_ind = start;
+
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    // Deletes up to the end of the word after the caret, or joins the next line at the end of a line
+    fn perform_delete_word_forward(&mut self) {
+        let Location { row, grapheme_index } = self.text_location;
+        if grapheme_index >= self.buffer.grapheme_count(row) {
+            self.perform_delete();
+            return;
+        }
+
+        let end = self.buffer.next_word_end(row, grapheme_index);
+        self.buffer.delete_range(row, grapheme_index..end);
+
+        self.set_needs_redraw(true);
+        self.scroll_into_view();
+    }
+
+    // Splits the line, starting the new one with the indentation of the old one, and one level more
+    // after the start of a block. Between a bracket pair, the closing one moves to a line of its own.
     fn perform_newline(&mut self) {
         let Location { row, grapheme_index } = self.text_location;
+        if let Some(line) = self.buffer.line(row) {
+            let before = line.get_graphemes(0..grapheme_index);
+            let after = line.get_graphemes(grapheme_index..usize::MAX);
+            let indentation = indent::leading_whitespace(&before).to_string();
+
+            let caret = if indent::opens_block(&before, self.file_extension()) {
+                let inner = format!("\n{indentation}{}", self.indent_unit.text());
+                let caret = self.buffer.insert_text(self.text_location, &inner);
+                if indent::closes_block(&before, &after) {
+                    self.buffer.insert_text(caret, &format!("\n{indentation}"));
+                }
+                caret
+            } else {
+                self.buffer.insert_text(self.text_location, &format!("\n{indentation}"))
+            };
+
+            self.text_location = caret;
+            self.max_grapheme_ind = caret.grapheme_index;
+            self.set_needs_redraw(true);
+            self.scroll_into_view();
+            return;
+        }
+
         let row_merge = self.buffer.height().min(row + 1);
 
         self.buffer.split_and_merge(row, grapheme_index, row_merge);
@@ -290,27 +1277,47 @@ impl UiComponent for View {
     }
 
     fn draw(&mut self, origin_row: usize) -> Result<(), std::io::Error> {
-        let Size { height, width } = self.size;
+        let height = self.size.height;
+        let width = self.text_width();
         let end_y = origin_row.saturating_add(height);
 
         let vertical_center = height / 3;
         let top = self.scroll_offset.row;
         let left = self.scroll_offset.col;
         let right = self.scroll_offset.col.saturating_add(width);
+        let welcome: Vec<String> = if self.buffer.is_empty() {
+            [Self::generate_welcome_message(width)]
+                .into_iter()
+                .chain(self.generate_recent_files_message(width))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let wrapped = if self.soft_wrap { self.wrapped_screen_lines() } else { Vec::new() };
 
         for row in origin_row..end_y {
-            let line_idx = row
-                .saturating_sub(origin_row)
-                .saturating_add(top);
-
-            if let Some(line) = self.buffer.get_line(line_idx, left..right) {
-                Self::render_text(row, &line);
-            } else if row == vertical_center && self.buffer.is_empty() {
-                Self::render_text(row, &Self::generate_welcome_message(width));
+            let screen_row = row.saturating_sub(origin_row);
+            let line_idx = screen_row.saturating_add(top);
+
+            if self.soft_wrap {
+                if let Some(line) = wrapped.get(screen_row) {
+                    self.render_text(row, line);
+                } else {
+                    self.render_text(row, "~");
+                }
+            } else if let Some(line) = self.styled_line(line_idx, left..right) {
+                self.render_text(row, &format!("{}{line}", self.gutter_text(Some(line_idx))));
+            } else if let Some(text) = row
+                .checked_sub(vertical_center)
+                .and_then(|ind| welcome.get(ind))
+            {
+                self.render_text(row, text);
             } else {
-                Self::render_text(row, "~");
+                self.render_text(row, "~");
             }
         }
+        self.drawn_caret_row = self.text_location.row;
 
         Ok(())
     }
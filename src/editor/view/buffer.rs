@@ -1,16 +1,27 @@
 use std::{fs::{self, File}, io::{Error, Write}, ops::Range};
 
-use super::{file_info::FileInfo, line::Line};
+use regex::Regex;
+
+use super::{
+    directory::{DirectoryChange, DirectoryListing},
+    file_info::FileInfo,
+    line::{DisplayStyle, Line},
+    whitespace::{self, TrimOnSave},
+    Location,
+};
 
 pub struct Buffer {
     pub lines: Vec<Line>,
     pub file_info: FileInfo,
     pub dirty: bool,
+    pub directory: Option<DirectoryListing>,
+    // whether each line has been edited since the buffer was loaded or saved
+    modified: Vec<bool>,
 }
 
 impl Default for Buffer {
     fn default() -> Self {
-        Buffer { lines: vec![], file_info: FileInfo::new(), dirty: false }
+        Buffer { lines: vec![], file_info: FileInfo::new(), dirty: false, directory: None, modified: vec![] }
     }
 }
 
@@ -19,38 +30,93 @@ impl Buffer {
         self.dirty
     }
 
+    #[allow(clippy::redundant_closure_for_method_calls)]
     pub fn load(&mut self, file_name: &str) -> Result<(), Error> {
         let content = fs::read_to_string(file_name)?;
         self.lines = content.lines().map(|x| x.into()).collect();
         self.file_info = FileInfo::from(file_name);
-        
+        self.modified = vec![false; self.lines.len()];
+
+        Ok(())
+    }
+
+    pub fn load_directory(&mut self, path: &str) -> Result<(), Error> {
+        let (listing, lines) = DirectoryListing::read(path.as_ref())?;
+        self.lines = lines.iter().map(|line| Line::from(line.as_str())).collect();
+        self.file_info = FileInfo::from(path);
+        self.directory = Some(listing);
+        self.modified = vec![false; self.lines.len()];
+
         Ok(())
     }
 
-    fn save_to_file(&self, file_info: &FileInfo) -> Result<(), Error> {
+    pub fn directory_changes(&self) -> Option<Result<Vec<DirectoryChange>, String>> {
+        self.directory.as_ref().map(|listing| {
+            let lines: Vec<String> = self.lines.iter().map(Line::as_string).collect();
+            listing.changes(&lines)
+        })
+    }
+
+    // Writes the lines, leaving out the trailing whitespace that saving trims. The buffer itself is only
+    // trimmed once the write succeeded.
+    fn save_to_file(&self, file_info: &FileInfo, trim: TrimOnSave) -> Result<(), Error> {
         if let Some(file_path) = file_info.get_path() {
             let mut file = File::create(file_path)?;
-            for line in &self.lines {
-                writeln!(file, "{line}")?;
+            for (row, line) in self.lines.iter().enumerate() {
+                let text = line.as_string();
+                if self.trims_on_save(row, trim) {
+                    writeln!(file, "{}", text.trim_end())?;
+                } else {
+                    writeln!(file, "{text}")?;
+                }
             }
         }
         Ok(())
     }
     
-    pub fn save_as(&mut self, file_name: &str) -> Result<(), Error> {
+    pub fn save_as(&mut self, file_name: &str, trim: TrimOnSave) -> Result<(), Error> {
         let file_info = FileInfo::from(file_name);
-        self.save_to_file(&file_info)?;
+        self.save_to_file(&file_info, trim)?;
         self.file_info = file_info;
-        self.dirty = false;
+        self.mark_saved(trim);
         Ok(())
     }
 
-    pub fn save(&mut self) -> Result<(), Error> {
-        self.save_to_file(&self.file_info)?;
-        self.dirty = false;
+    pub fn save(&mut self, trim: TrimOnSave) -> Result<(), Error> {
+        self.save_to_file(&self.file_info, trim)?;
+        self.mark_saved(trim);
         Ok(())
     }
 
+    fn trims_on_save(&self, row: usize, trim: TrimOnSave) -> bool {
+        match trim {
+            TrimOnSave::Off => false,
+            TrimOnSave::Modified => self.is_modified(row),
+            TrimOnSave::All => true,
+        }
+    }
+
+    // Trims the buffer the way it was written, then counts every line as unmodified
+    fn mark_saved(&mut self, trim: TrimOnSave) {
+        if trim != TrimOnSave::Off {
+            self.trim_trailing_whitespace(0..self.height(), trim == TrimOnSave::Modified);
+        }
+        self.dirty = false;
+        self.modified = vec![false; self.lines.len()];
+    }
+
+    fn is_modified(&self, row: usize) -> bool {
+        self.modified.get(row).copied().unwrap_or(true)
+    }
+
+    // Counts the rows as edited, e.g. for trimming only modified lines on save
+    fn mark_modified(&mut self, rows: Range<usize>) {
+        for modified in self.modified.iter_mut().take(rows.end).skip(rows.start) {
+            *modified = true;
+        }
+        self.dirty = true;
+    }
+
     pub const fn is_file_loaded(&self) -> bool {
         self.file_info.has_path()
     }
@@ -59,41 +125,133 @@ impl Buffer {
         self.lines.is_empty()
     }
 
-    pub fn get_line(&self, row: usize, range: Range<usize>) -> Option<String> {
+    pub fn row_width_until(&self, row: usize, grapheme_ind: usize, style: DisplayStyle) -> usize {
         self.lines
             .get(row)
-            .map_or(None, |line| Some(line.get(range)))
+            .map_or(0, |line| line.width_until(grapheme_ind, style))
     }
 
-    pub fn row_width_until(&self, row: usize, grapheme_ind: usize) -> usize {
+    pub fn get_valid_grapheme_ind(&self, row: usize, grapheme_ind: usize) -> usize {
         self.lines
             .get(row)
-            .map_or(0, |line| line.width_until(grapheme_ind))
+            .map_or(0, |line| line.grapheme_count().min(grapheme_ind))
     }
 
-    pub fn get_valid_grapheme_ind(&self, row: usize, grapheme_ind: usize) -> usize {
+    pub fn line(&self, row: usize) -> Option<&Line> {
+        self.lines.get(row)
+    }
+
+    // The text from `start` up to `end`, with the lines joined by newlines
+    pub fn text_between(&self, start: Location, end: Location) -> String {
+        (start.row..=end.row)
+            .filter_map(|row| self.lines.get(row).map(|line| (row, line)))
+            .map(|(row, line)| {
+                let from = if row == start.row { start.grapheme_index } else { 0 };
+                let to = if row == end.row { end.grapheme_index } else { line.grapheme_count() };
+                line.get_graphemes(from..to)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Removes the text from `start` up to `end`, joining the lines it spans
+    pub fn delete_between(&mut self, start: Location, end: Location) {
+        if start >= end || start.row >= self.height() {
+            return;
+        }
+
+        let end_row = end.row.min(self.height() - 1);
+        let head = self.lines[start.row].get_graphemes(0..start.grapheme_index);
+        let tail = if end.row == end_row {
+            self.lines[end_row].get_graphemes(end.grapheme_index..usize::MAX)
+        } else {
+            String::new()
+        };
+
+        self.lines.splice(start.row..=end_row, [Line::from(format!("{head}{tail}").as_str())]);
+        self.modified.splice(start.row..=end_row, [true]);
+        self.dirty = true;
+    }
+
+    // Inserts text that may span several lines, returning the location right after it
+    pub fn insert_text(&mut self, at: Location, text: &str) -> Location {
+        if text.is_empty() {
+            return at;
+        }
+        let row = at.row.min(self.height());
+        if row == self.height() {
+            self.lines.push(Line::default());
+            self.modified.push(true);
+        }
+
+        let line = &self.lines[row];
+        let head = line.get_graphemes(0..at.grapheme_index);
+        let tail = line.get_graphemes(at.grapheme_index..usize::MAX);
+
+        let parts: Vec<&str> = text.split('\n').collect();
+        let last = parts.len() - 1;
+        let end_index = Line::from(parts[last]).grapheme_count()
+            + if last == 0 { Line::from(head.as_str()).grapheme_count() } else { 0 };
+
+        let new_lines = parts.iter().enumerate().map(|(ind, part)| {
+            let prefix = if ind == 0 { head.as_str() } else { "" };
+            let suffix = if ind == last { tail.as_str() } else { "" };
+            Line::from(format!("{prefix}{part}{suffix}").as_str())
+        });
+        self.lines.splice(row..=row, new_lines.collect::<Vec<_>>());
+        self.modified.splice(row..=row, vec![true; parts.len()]);
+        self.dirty = true;
+
+        Location { row: row + last, grapheme_index: end_index }
+    }
+
+    // The whole lines in the range, each ending with a newline
+    pub fn lines_text(&self, rows: Range<usize>) -> String {
         self.lines
-            .get(row)
-            .map_or(0, |line| line.grapheme_count().min(grapheme_ind))
+            .get(rows.start.min(self.height())..rows.end.min(self.height()))
+            .map_or_else(String::new, |lines| lines.iter().flat_map(|line| [line.as_string(), "\n".to_string()]).collect())
+    }
+
+    pub fn delete_lines(&mut self, rows: Range<usize>) {
+        let rows = rows.start.min(self.height())..rows.end.min(self.height());
+        if !rows.is_empty() {
+            self.lines.drain(rows.clone());
+            self.modified.drain(rows);
+            self.dirty = true;
+        }
+    }
+
+    // Replaces the rows with the given lines, which may be more or fewer
+    pub fn replace_lines(&mut self, rows: Range<usize>, lines: &[String]) {
+        let rows = rows.start.min(self.height())..rows.end.min(self.height());
+        self.lines.splice(rows.clone(), lines.iter().map(|line| Line::from(line.as_str())));
+        self.modified.splice(rows, vec![true; lines.len()]);
+        self.dirty = true;
     }
 
     pub fn height(&self) -> usize {
         self.lines.len()
     }
 
+    #[allow(clippy::redundant_closure_for_method_calls)]
     pub fn grapheme_count(&self, row: usize) -> usize {
         self.lines.get(row).map_or(0, |line| line.grapheme_count())
     }
     
+    #[allow(clippy::redundant_closure_for_method_calls, clippy::single_match_else)]
     pub fn insert_char(&mut self, c: char, row: usize, grapheme_index: usize, has_len_increased: &mut bool) {
         let old_len = self.lines.get(row).map_or(0, |line| line.grapheme_count());
 
         match self.lines.get_mut(row) {
-            Some(line) => line.insert_char(c, grapheme_index),
+            Some(line) => {
+                line.insert_char(c, grapheme_index);
+                self.mark_modified(row..row + 1);
+            },
             None => {
                 let mut s = String::new();
                 s.push(c);
                 self.lines.push(Line::from(s.as_str()));
+                self.modified.push(true);
             },
         }
 
@@ -104,10 +262,124 @@ impl Buffer {
     }
 
     pub fn delete_grapheme_at(&mut self, row: usize, grapheme_index: usize) {
-        self.lines.get_mut(row).map_or_else(|| {}, |line| line.delete_grapheme_at(grapheme_index));
+        if let Some(line) = self.lines.get_mut(row) {
+            line.delete_grapheme_at(grapheme_index);
+            self.mark_modified(row..row + 1);
+        }
         self.dirty = true;
     }
 
+    pub fn previous_word_start(&self, row: usize, grapheme_index: usize) -> usize {
+        self.lines
+            .get(row)
+            .map_or(0, |line| line.previous_word_start(grapheme_index))
+    }
+
+    pub fn next_word_end(&self, row: usize, grapheme_index: usize) -> usize {
+        self.lines.get(row).map_or(0, |line| line.next_word_end(grapheme_index))
+    }
+
+    pub fn delete_range(&mut self, row: usize, range: Range<usize>) {
+        if let Some(line) = self.lines.get_mut(row) {
+            line.delete_range(range);
+            self.mark_modified(row..row + 1);
+        }
+    }
+
+    // Replaces the pattern in the rows, only its first occurrence in each line unless `global`.
+    // Returns how many occurrences got replaced.
+    pub fn substitute(&mut self, rows: Range<usize>, pattern: &Regex, replacement: &str, global: bool) -> usize {
+        let mut count = 0;
+
+        for (line, modified) in self.lines.iter_mut().zip(&mut self.modified).take(rows.end).skip(rows.start) {
+            let text = line.as_string();
+            let matches = pattern.find_iter(&text).count();
+            if matches == 0 {
+                continue;
+            }
+
+            let (new_text, replaced) = if global {
+                (pattern.replace_all(&text, replacement), matches)
+            } else {
+                (pattern.replacen(&text, 1, replacement), 1)
+            };
+            *line = Line::from(new_text.as_ref());
+            *modified = true;
+            count += replaced;
+        }
+
+        if count > 0 {
+            self.dirty = true;
+        }
+        count
+    }
+
+    // Rewrites the rows that pass the filter, which also gets whether the row is modified. Returns how many of
+    // them changed.
+    fn rewrite_lines(
+        &mut self,
+        rows: Range<usize>,
+        filter: impl Fn(&str, bool) -> bool,
+        rewrite: impl Fn(&str) -> String,
+    ) -> usize {
+        let mut count = 0;
+        for (line, modified) in self.lines.iter_mut().zip(&mut self.modified).take(rows.end).skip(rows.start) {
+            let text = line.as_string();
+            if !filter(&text, *modified) {
+                continue;
+            }
+            let new_text = rewrite(&text);
+            if new_text != text {
+                *line = Line::from(new_text.as_str());
+                *modified = true;
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            self.dirty = true;
+        }
+        count
+    }
+
+    // Redoes the indentation of the rows with tabs, or with spaces only. Returns how many lines changed.
+    pub fn retab(&mut self, rows: Range<usize>, tab_width: usize, to_tabs: bool) -> usize {
+        self.rewrite_lines(rows, |_, _| true, |text| whitespace::retab(text, tab_width, to_tabs))
+    }
+
+    // Removes whitespace at the end of the rows, or only of those modified since the buffer was loaded or saved.
+    // Returns how many lines changed.
+    pub fn trim_trailing_whitespace(&mut self, rows: Range<usize>, only_modified: bool) -> usize {
+        self.rewrite_lines(rows, |_, modified| !only_modified || modified, |text| text.trim_end().to_string())
+    }
+
+    // Removes the blank lines in the rows that follow another blank line, returning how many were removed
+    pub fn squeeze_blank_lines(&mut self, rows: Range<usize>) -> usize {
+        let rows = rows.start.min(self.height())..rows.end.min(self.height());
+        let old_height = self.height();
+        let mut previous_blank = false;
+        let mut row = rows.start;
+        let mut end = rows.end;
+
+        while row < end {
+            let blank = whitespace::is_blank(&self.lines[row].as_string());
+            if blank && previous_blank {
+                self.lines.remove(row);
+                self.modified.remove(row);
+                end -= 1;
+            } else {
+                row += 1;
+            }
+            previous_blank = blank;
+        }
+
+        let removed = old_height - self.height();
+        if removed > 0 {
+            self.dirty = true;
+        }
+        removed
+    }
+
     pub fn delete_and_merge(&mut self, row_del: usize, row_merge: usize) {
         let del_line_as_str = self.lines
             .get_mut(row_del)
@@ -117,8 +389,9 @@ impl Buffer {
             );
 
         self.lines.get_mut(row_merge).unwrap().append_str(&del_line_as_str);
+        self.mark_modified(row_merge..row_merge + 1);
         self.lines.remove(row_del);
-        self.dirty = true;
+        self.modified.remove(row_del);
     }
 
     pub fn split_and_merge(&mut self, row_split: usize, split_ind: usize, row_merge: usize) {
@@ -128,7 +401,8 @@ impl Buffer {
                 Vec::new(), 
                 |line| line.split(split_ind)
             );
+        self.mark_modified(row_split..row_split + 1);
         self.lines.insert(row_merge, Line::new(splitted_fragments));
-        self.dirty = true;
+        self.modified.insert(row_merge, true);
     }
 }
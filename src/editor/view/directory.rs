@@ -0,0 +1,437 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs,
+    io::Error,
+    path::{Path, PathBuf},
+};
+
+use log::error;
+
+// A directory shown as a buffer, one numbered entry per line.
+// The numbers tie edited lines back to the entries they started out as.
+pub struct DirectoryListing {
+    root: PathBuf,
+    entries: Vec<String>,
+}
+
+pub enum DirectoryChange {
+    Delete(PathBuf),
+    Rename(PathBuf, PathBuf),
+    Create(PathBuf),
+    CreateDirectory(PathBuf),
+}
+
+impl fmt::Display for DirectoryChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Delete(path) if path.is_dir() => {
+                write!(f, "delete {}/ and everything in it", path.display())
+            }
+            Self::Delete(path) => write!(f, "delete {}", path.display()),
+            Self::Rename(from, to) => write!(f, "rename {} -> {}", from.display(), to.display()),
+            Self::Create(path) => write!(f, "create {}", path.display()),
+            Self::CreateDirectory(path) => write!(f, "create {}/", path.display()),
+        }
+    }
+}
+
+impl DirectoryListing {
+    // Reads the directory, returning the listing and the lines to show for it
+    pub fn read(root: &Path) -> Result<(Self, Vec<String>), Error> {
+        let mut entries: Vec<(bool, String)> = fs::read_dir(root)?
+            .filter_map(Result::ok)
+            .map(|entry| {
+                let is_dir = entry.path().is_dir();
+                let mut name = entry.file_name().to_string_lossy().to_string();
+                if is_dir {
+                    name.push('/');
+                }
+                (is_dir, name)
+            })
+            .collect();
+        entries.sort_by(|(a_dir, a_name), (b_dir, b_name)| b_dir.cmp(a_dir).then_with(|| a_name.cmp(b_name)));
+
+        let entries: Vec<String> = entries.into_iter().map(|(_, name)| name).collect();
+        let lines = entries
+            .iter()
+            .enumerate()
+            .map(|(ind, name)| format!("{:>4}  {name}", ind.saturating_add(1)))
+            .collect();
+
+        Ok((
+            Self {
+                root: root.to_path_buf(),
+                entries,
+            },
+            lines,
+        ))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // Splits an edited line into its entry number, if it still starts with the number exactly as `read` wrote
+    // it, and the file name, which is kept as it is, spaces included
+    fn parse_line(line: &str) -> (Option<usize>, &str) {
+        let after_digits = line.trim_start_matches(' ').trim_start_matches(|c: char| c.is_ascii_digit());
+        let digits_end = line.len() - after_digits.len();
+        let id = line[..digits_end].trim_start().parse::<usize>().ok();
+        match (id, line[digits_end..].strip_prefix("  ")) {
+            (Some(id), Some(name)) if format!("{id:>4}") == line[..digits_end] => (Some(id), name),
+            _ => (None, line),
+        }
+    }
+
+    // Works out what to do on disk to turn the original listing into the edited one
+    pub fn changes(&self, lines: &[String]) -> Result<Vec<DirectoryChange>, String> {
+        let mut kept: HashMap<usize, &str> = HashMap::new();
+        let mut created: Vec<&str> = Vec::new();
+        let mut targets: HashSet<&str> = HashSet::new();
+
+        for line in lines {
+            let (id, name) = Self::parse_line(line);
+            if name.trim().is_empty() {
+                continue;
+            }
+            if !targets.insert(name.trim_end_matches('/')) {
+                return Err(format!("{name} is listed more than once"));
+            }
+
+            match id {
+                Some(id) if id == 0 || id > self.entries.len() => {
+                    return Err(format!("There is no entry number {id}"));
+                }
+                Some(id) => {
+                    if kept.insert(id, name).is_some() {
+                        return Err(format!("Entry number {id} is listed more than once"));
+                    }
+                }
+                None => created.push(name),
+            }
+        }
+
+        let mut changes = Vec::new();
+        for (ind, original) in self.entries.iter().enumerate() {
+            let from = self.root.join(original.trim_end_matches('/'));
+            match kept.get(&ind.saturating_add(1)) {
+                None => changes.push(DirectoryChange::Delete(from)),
+                Some(name) if name.trim_end_matches('/') != original.trim_end_matches('/') => {
+                    changes.push(DirectoryChange::Rename(from, self.root.join(name.trim_end_matches('/'))));
+                }
+                Some(_) => {}
+            }
+        }
+        for name in created {
+            let path = self.root.join(name.trim_end_matches('/'));
+            if name.ends_with('/') {
+                changes.push(DirectoryChange::CreateDirectory(path));
+            } else {
+                changes.push(DirectoryChange::Create(path));
+            }
+        }
+
+        Ok(changes)
+    }
+
+    // Applies the changes, or none of them: every target is checked before anything is touched, deleted
+    // entries and rename sources first move to temporary names, which also lets two names be swapped, and
+    // the deleted ones are only removed once everything else has worked. If a step fails, the steps
+    // taken so far are taken back.
+    pub fn apply(changes: &[DirectoryChange]) -> Result<(), Error> {
+        Self::check(changes)?;
+
+        let mut steps = Vec::new();
+        let result = Self::apply_steps(changes, &mut steps);
+        let deleted = match result {
+            Ok(deleted) => deleted,
+            Err(err) => {
+                undo(steps);
+                return Err(err);
+            }
+        };
+
+        for path in deleted {
+            let removed = if path.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+            removed.map_err(|err| Error::other(format!("Could not remove {}: {err}", path.display())))?;
+        }
+        Ok(())
+    }
+
+    // Makes sure every path to rename to or create is free, or freed by the changes, and can be created
+    fn check(changes: &[DirectoryChange]) -> Result<(), Error> {
+        let vacated: HashSet<&Path> = changes
+            .iter()
+            .filter_map(|change| match change {
+                DirectoryChange::Delete(path) | DirectoryChange::Rename(path, _) => Some(path.as_path()),
+                DirectoryChange::Create(_) | DirectoryChange::CreateDirectory(_) => None,
+            })
+            .collect();
+        let exists = |path: &Path| path.symlink_metadata().is_ok();
+
+        for change in changes {
+            let target = match change {
+                DirectoryChange::Rename(_, path)
+                | DirectoryChange::Create(path)
+                | DirectoryChange::CreateDirectory(path) => path,
+                DirectoryChange::Delete(_) => continue,
+            };
+            if exists(target) && !vacated.contains(target.as_path()) {
+                return Err(Error::other(format!("{} already exists", target.display())));
+            }
+            // the closest existing parent has to be a directory, or be moved out of the way
+            if let Some(parent) = target.ancestors().skip(1).find(|parent| exists(parent)) {
+                if !parent.is_dir() && !vacated.contains(parent) {
+                    let message = format!("Cannot create {}: {} is not a directory", target.display(), parent.display());
+                    return Err(Error::other(message));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Takes every step but the final removal of deleted entries, recording each one so that it can be
+    // undone. Returns the temporary paths the deleted entries were moved to.
+    fn apply_steps(changes: &[DirectoryChange], steps: &mut Vec<Step>) -> Result<Vec<PathBuf>, Error> {
+        let mut deleted = Vec::new();
+        let mut pending = Vec::new();
+        for (ind, change) in changes.iter().enumerate() {
+            let (from, to) = match change {
+                DirectoryChange::Delete(from) => (from, None),
+                DirectoryChange::Rename(from, to) => (from, Some(to)),
+                DirectoryChange::Create(_) | DirectoryChange::CreateDirectory(_) => continue,
+            };
+            let temporary = from.with_file_name(format!(".rename-{}-{ind}", std::process::id()));
+            move_path(from, &temporary, steps)?;
+            match to {
+                Some(to) => pending.push((temporary, to)),
+                None => deleted.push(temporary),
+            }
+        }
+
+        for (temporary, to) in pending {
+            create_parents(to, steps)?;
+            move_path(&temporary, to, steps)?;
+        }
+
+        for change in changes {
+            match change {
+                DirectoryChange::Create(path) => {
+                    create_parents(path, steps)?;
+                    fs::File::create_new(path)?;
+                    steps.push(Step::CreatedFile(path.clone()));
+                }
+                DirectoryChange::CreateDirectory(path) => {
+                    create_parents(path, steps)?;
+                    fs::create_dir(path)?;
+                    steps.push(Step::CreatedDirectory(path.clone()));
+                }
+                DirectoryChange::Delete(_) | DirectoryChange::Rename(..) => {}
+            }
+        }
+
+        Ok(deleted)
+    }
+}
+
+// Something done on disk while applying changes, and so something to take back if a later step fails
+enum Step {
+    Moved(PathBuf, PathBuf),
+    CreatedFile(PathBuf),
+    CreatedDirectory(PathBuf),
+}
+
+fn move_path(from: &Path, to: &Path, steps: &mut Vec<Step>) -> Result<(), Error> {
+    fs::rename(from, to)?;
+    steps.push(Step::Moved(from.to_path_buf(), to.to_path_buf()));
+    Ok(())
+}
+
+// Creates the missing directories above the path, one at a time so that each can be taken back
+fn create_parents(path: &Path, steps: &mut Vec<Step>) -> Result<(), Error> {
+    let missing: Vec<&Path> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|parent| !parent.as_os_str().is_empty() && parent.symlink_metadata().is_err())
+        .collect();
+    for parent in missing.into_iter().rev() {
+        fs::create_dir(parent)?;
+        steps.push(Step::CreatedDirectory(parent.to_path_buf()));
+    }
+    Ok(())
+}
+
+// Takes the steps back, last first. This is already recovering from an error, so it carries on past
+// steps that can't be undone.
+fn undo(steps: Vec<Step>) {
+    for step in steps.into_iter().rev() {
+        let undone = match step {
+            Step::Moved(from, to) => fs::rename(&to, &from),
+            Step::CreatedFile(path) => fs::remove_file(&path),
+            Step::CreatedDirectory(path) => fs::remove_dir(&path),
+        };
+        if let Err(err) = undone {
+            error!("Could not take back a directory change: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(entries: &[&str]) -> DirectoryListing {
+        DirectoryListing {
+            root: PathBuf::from("/listing"),
+            entries: entries.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    fn changes(listing: &DirectoryListing, lines: &[&str]) -> Result<Vec<String>, String> {
+        let lines: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        listing.changes(&lines).map(|changes| changes.iter().map(ToString::to_string).collect())
+    }
+
+    // A fresh directory holding the files, removed again when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let path = std::env::temp_dir().join(format!("directory-test-{}-{name}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            for (file, content) in files {
+                fs::write(path.join(file), content).unwrap();
+            }
+            Self(path)
+        }
+
+        fn read(&self, file: &str) -> String {
+            fs::read_to_string(self.0.join(file)).unwrap()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn parse_line_reads_the_number_as_written() {
+        assert_eq!(DirectoryListing::parse_line("   1  a.txt"), (Some(1), "a.txt"));
+        assert_eq!(DirectoryListing::parse_line("  12  src/"), (Some(12), "src/"));
+        assert_eq!(DirectoryListing::parse_line("1234  a.txt"), (Some(1234), "a.txt"));
+        assert_eq!(DirectoryListing::parse_line("12345  a.txt"), (Some(12345), "a.txt"));
+    }
+
+    #[test]
+    fn parse_line_keeps_the_name_as_it_is() {
+        assert_eq!(DirectoryListing::parse_line("   1   two  spaces "), (Some(1), " two  spaces "));
+        assert_eq!(DirectoryListing::parse_line("   3  7  wonders.md"), (Some(3), "7  wonders.md"));
+    }
+
+    #[test]
+    fn parse_line_treats_other_lines_as_new_names() {
+        assert_eq!(DirectoryListing::parse_line("2024 notes.md"), (None, "2024 notes.md"));
+        assert_eq!(DirectoryListing::parse_line("1  a.txt"), (None, "1  a.txt"));
+        assert_eq!(DirectoryListing::parse_line("   1 a.txt"), (None, "   1 a.txt"));
+        assert_eq!(DirectoryListing::parse_line("   01  a.txt"), (None, "   01  a.txt"));
+        assert_eq!(DirectoryListing::parse_line("notes.md"), (None, "notes.md"));
+    }
+
+    #[test]
+    fn unchanged_listing_has_no_changes() {
+        let listing = listing(&["src/", "a.txt"]);
+        assert_eq!(changes(&listing, &["   1  src/", "   2  a.txt", ""]), Ok(vec![]));
+    }
+
+    #[test]
+    fn edited_lines_map_to_changes() {
+        let listing = listing(&["src/", "a.txt", "b.txt", "c.txt"]);
+        let lines = ["   1  lib/", "   3  a.txt", "   2  b.txt", "docs/", "2024 notes.md"];
+        assert_eq!(
+            changes(&listing, &lines),
+            Ok(vec![
+                "rename /listing/src -> /listing/lib".to_string(),
+                "rename /listing/a.txt -> /listing/b.txt".to_string(),
+                "rename /listing/b.txt -> /listing/a.txt".to_string(),
+                "delete /listing/c.txt".to_string(),
+                "create /listing/docs/".to_string(),
+                "create /listing/2024 notes.md".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn conflicting_lines_are_rejected() {
+        let listing = listing(&["a.txt", "b.txt"]);
+        assert_eq!(
+            changes(&listing, &["   1  a.txt", "   2  a.txt"]),
+            Err("a.txt is listed more than once".to_string())
+        );
+        assert_eq!(
+            changes(&listing, &["   1  a.txt", "   1  c.txt"]),
+            Err("Entry number 1 is listed more than once".to_string())
+        );
+        assert_eq!(changes(&listing, &["   3  c.txt"]), Err("There is no entry number 3".to_string()));
+        assert_eq!(changes(&listing, &["   0  c.txt"]), Err("There is no entry number 0".to_string()));
+    }
+
+    #[test]
+    fn apply_swaps_names() {
+        let dir = TempDir::new("swap", &[("a.txt", "a"), ("b.txt", "b")]);
+        let changes = [
+            DirectoryChange::Rename(dir.0.join("a.txt"), dir.0.join("b.txt")),
+            DirectoryChange::Rename(dir.0.join("b.txt"), dir.0.join("a.txt")),
+        ];
+        DirectoryListing::apply(&changes).unwrap();
+        assert_eq!(dir.read("a.txt"), "b");
+        assert_eq!(dir.read("b.txt"), "a");
+    }
+
+    #[test]
+    fn apply_renames_onto_a_deleted_name() {
+        let dir = TempDir::new("replace", &[("a.txt", "a"), ("b.txt", "b")]);
+        let changes = [
+            DirectoryChange::Rename(dir.0.join("a.txt"), dir.0.join("b.txt")),
+            DirectoryChange::Delete(dir.0.join("b.txt")),
+            DirectoryChange::Create(dir.0.join("new/c.txt")),
+        ];
+        DirectoryListing::apply(&changes).unwrap();
+        assert_eq!(dir.read("b.txt"), "a");
+        assert!(!dir.0.join("a.txt").exists());
+        assert!(dir.0.join("new/c.txt").is_file());
+    }
+
+    #[test]
+    fn apply_touches_nothing_when_a_target_exists() {
+        let dir = TempDir::new("exists", &[("a.txt", "a"), ("b.txt", "b")]);
+        let changes = [
+            DirectoryChange::Delete(dir.0.join("a.txt")),
+            DirectoryChange::Create(dir.0.join("b.txt")),
+        ];
+        assert!(DirectoryListing::apply(&changes).is_err());
+        assert_eq!(dir.read("a.txt"), "a");
+        assert_eq!(dir.read("b.txt"), "b");
+    }
+
+    #[test]
+    fn apply_takes_back_the_steps_before_a_failure() {
+        let dir = TempDir::new("undo", &[("a.txt", "a"), ("b.txt", "b")]);
+        let changes = [
+            DirectoryChange::Rename(dir.0.join("a.txt"), dir.0.join("c.txt")),
+            DirectoryChange::Delete(dir.0.join("b.txt")),
+            DirectoryChange::Create(dir.0.join("bad\0name")),
+        ];
+        assert!(DirectoryListing::apply(&changes).is_err());
+        let mut names: Vec<String> = fs::read_dir(&dir.0)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a.txt", "b.txt"]);
+        assert_eq!(dir.read("a.txt"), "a");
+    }
+}
@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+// Which line numbers the gutter shows
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineNumbers {
+    #[default]
+    Off,
+    Absolute,
+    // the distance of each line from the caret's line
+    Relative,
+    // relative numbers, with the absolute one on the caret's line
+    Hybrid,
+}
+
+impl LineNumbers {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Absolute => "absolute",
+            Self::Relative => "relative",
+            Self::Hybrid => "hybrid",
+        }
+    }
+}
+
+impl FromStr for LineNumbers {
+    type Err = String;
+
+    // `true` and `false` still work from when line numbers could only be turned on or off
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" | "false" | "no" => Ok(Self::Off),
+            "absolute" | "on" | "true" | "yes" => Ok(Self::Absolute),
+            "relative" => Ok(Self::Relative),
+            "hybrid" => Ok(Self::Hybrid),
+            _ => Err(format!("expected off, absolute, relative or hybrid, got {value:?}")),
+        }
+    }
+}
+
+// A column of the gutter. Further columns, such as diagnostics, version control or fold markers,
+// are added as variants that know their width and what to draw next to each line.
+pub enum GutterColumn {
+    LineNumbers(LineNumbers),
+}
+
+impl GutterColumn {
+    // The width for a buffer with that many lines, 0 while the column shows nothing
+    fn width(&self, line_count: usize) -> usize {
+        match self {
+            Self::LineNumbers(LineNumbers::Off) => 0,
+            Self::LineNumbers(_) => line_count.max(1).to_string().len(),
+        }
+    }
+
+    // What the column shows next to the line, or next to a screen row without one, e.g. a wrapped
+    // line's continuation
+    fn cell(&self, row: Option<usize>, caret_row: usize, width: usize) -> String {
+        match *self {
+            Self::LineNumbers(mode) => {
+                let Some(row) = row else {
+                    return " ".repeat(width);
+                };
+                match mode {
+                    LineNumbers::Off => String::new(),
+                    LineNumbers::Absolute => format!("{:>width$}", row + 1),
+                    LineNumbers::Hybrid if row == caret_row => format!("{:<width$}", row + 1),
+                    LineNumbers::Relative | LineNumbers::Hybrid => format!("{:>width$}", row.abs_diff(caret_row)),
+                }
+            }
+        }
+    }
+
+    // Whether the column changes when the caret moves to another line
+    const fn follows_caret(&self) -> bool {
+        matches!(self, Self::LineNumbers(LineNumbers::Relative | LineNumbers::Hybrid))
+    }
+}
+
+// The columns drawn left of the text
+#[derive(Default)]
+pub struct Gutter {
+    columns: Vec<GutterColumn>,
+}
+
+impl Gutter {
+    pub fn set_line_numbers(&mut self, mode: LineNumbers) {
+        let column = GutterColumn::LineNumbers(mode);
+        match self.columns.iter_mut().find(|column| matches!(column, GutterColumn::LineNumbers(_))) {
+            Some(existing) => *existing = column,
+            None => self.columns.insert(0, column),
+        }
+    }
+
+    // The width of the columns and the space that separates them from the text, 0 if none shows anything
+    pub fn width(&self, line_count: usize) -> usize {
+        let width: usize = self.columns.iter().map(|column| column.width(line_count)).sum();
+        if width == 0 {
+            0
+        } else {
+            width + 1
+        }
+    }
+
+    pub fn follows_caret(&self) -> bool {
+        self.columns.iter().any(GutterColumn::follows_caret)
+    }
+
+    // The gutter of a screen row, showing the line, or nothing line-specific for rows without one
+    pub fn render(&self, row: Option<usize>, caret_row: usize, line_count: usize) -> String {
+        if self.width(line_count) == 0 {
+            return String::new();
+        }
+        let mut text: String = self
+            .columns
+            .iter()
+            .map(|column| column.cell(row, caret_row, column.width(line_count)))
+            .collect();
+        text.push(' ');
+        text
+    }
+}
@@ -0,0 +1,194 @@
+use std::str::FromStr;
+
+use super::line::Line;
+
+// Whether indentation uses tabs or spaces
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndentPolicy {
+    // whatever the file already uses, tabs if it isn't indented yet
+    #[default]
+    Auto,
+    Tabs,
+    Spaces,
+}
+
+impl IndentPolicy {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Tabs => "tabs",
+            Self::Spaces => "spaces",
+        }
+    }
+}
+
+impl FromStr for IndentPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Self::Auto),
+            "tabs" => Ok(Self::Tabs),
+            "spaces" => Ok(Self::Spaces),
+            _ => Err(format!("expected auto, tabs or spaces, got {value:?}")),
+        }
+    }
+}
+
+// One level of indentation
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndentUnit {
+    #[default]
+    Tab,
+    Spaces(usize),
+}
+
+impl IndentUnit {
+    pub fn resolve(policy: IndentPolicy, lines: &[Line], tab_width: usize) -> Self {
+        match policy {
+            IndentPolicy::Tabs => Self::Tab,
+            IndentPolicy::Spaces => Self::Spaces(tab_width.max(1)),
+            IndentPolicy::Auto => detect(lines, tab_width).unwrap_or_default(),
+        }
+    }
+
+    // The text of one level of indentation
+    pub fn text(self) -> String {
+        match self {
+            Self::Tab => "\t".to_string(),
+            Self::Spaces(width) => " ".repeat(width),
+        }
+    }
+}
+
+// How the lines are indented: with tabs if more lines start with a tab than with a space, otherwise with
+// the number of spaces indentation most often grows by
+fn detect(lines: &[Line], tab_width: usize) -> Option<IndentUnit> {
+    let mut tab_lines = 0;
+    let mut space_lines = 0;
+    // how often the indentation grows by 2 to 8 spaces from one line to the next
+    let mut steps = [0; 9];
+    let mut previous = 0;
+
+    for line in lines {
+        let text = line.as_string();
+        if text.trim().is_empty() {
+            continue;
+        }
+        if text.starts_with('\t') {
+            tab_lines += 1;
+            previous = 0;
+            continue;
+        }
+
+        let spaces = text.len() - text.trim_start_matches(' ').len();
+        if spaces > 0 {
+            space_lines += 1;
+        }
+        let step = spaces.saturating_sub(previous);
+        if (2..steps.len()).contains(&step) {
+            steps[step] += 1;
+        }
+        previous = spaces;
+    }
+
+    if tab_lines == 0 && space_lines == 0 {
+        None
+    } else if tab_lines > space_lines {
+        Some(IndentUnit::Tab)
+    } else {
+        let width = (2..steps.len()).rev().max_by_key(|&width| steps[width]).filter(|&width| steps[width] > 0);
+        Some(IndentUnit::Spaces(width.unwrap_or(tab_width.max(1))))
+    }
+}
+
+// The whitespace the line starts with
+pub fn leading_whitespace(text: &str) -> &str {
+    &text[..text.len() - text.trim_start().len()]
+}
+
+// What a line ends with when the next one starts a block, by file extension
+fn block_openers(extension: Option<&str>) -> &'static [char] {
+    match extension {
+        None | Some("txt" | "md") => &[],
+        Some("py") => &[':', '(', '[', '{'],
+        Some("yaml" | "yml") => &[':'],
+        _ => &['(', '[', '{'],
+    }
+}
+
+pub fn opens_block(text: &str, extension: Option<&str>) -> bool {
+    text.trim_end().ends_with(block_openers(extension))
+}
+
+// Whether the text starts by closing the block opened right before it, e.g. the `}` in `{}`
+pub fn closes_block(before: &str, after: &str) -> bool {
+    let pair = |open, close| before.trim_end().ends_with(open) && after.trim_start().starts_with(close);
+    pair('(', ')') || pair('[', ']') || pair('{', '}')
+}
+
+// Whether typing the character closes a block, which takes back the indentation added after its opener
+pub fn is_block_closer(c: char, extension: Option<&str>) -> bool {
+    let opener = match c {
+        ')' => '(',
+        ']' => '[',
+        '}' => '{',
+        _ => return false,
+    };
+    block_openers(extension).contains(&opener)
+}
+
+// How many graphemes to remove from the start of the line to take away one level of indentation
+pub fn dedent_len(text: &str, unit: IndentUnit, tab_width: usize) -> usize {
+    if text.starts_with('\t') {
+        return 1;
+    }
+    let width = match unit {
+        IndentUnit::Tab => tab_width,
+        IndentUnit::Spaces(width) => width,
+    };
+    text.chars().take(width).take_while(|c| *c == ' ').count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect_in(lines: &[&str], tab_width: usize) -> Option<IndentUnit> {
+        let lines: Vec<Line> = lines.iter().map(|line| Line::from(*line)).collect();
+        detect(&lines, tab_width)
+    }
+
+    #[test]
+    fn detect_finds_the_most_common_step() {
+        let rust = ["fn main() {", "    if x {", "        y();", "    }", "}"];
+        assert!(detect_in(&rust, 8) == Some(IndentUnit::Spaces(4)));
+        assert!(detect_in(&["a:", "  b:", "    c: 1", "  d: 2"], 4) == Some(IndentUnit::Spaces(2)));
+    }
+
+    #[test]
+    fn detect_prefers_tabs_when_more_lines_use_them() {
+        assert!(detect_in(&["a {", "\tb", "\tc", "  d", "}"], 4) == Some(IndentUnit::Tab));
+    }
+
+    #[test]
+    fn detect_falls_back_to_the_tab_width() {
+        assert!(detect_in(&["a", " b"], 8) == Some(IndentUnit::Spaces(8)));
+        assert!(detect_in(&["a", "", "b"], 4).is_none());
+    }
+
+    #[test]
+    fn dedent_len_takes_one_level() {
+        assert_eq!(dedent_len("\t\tx", IndentUnit::Spaces(4), 4), 1);
+        assert_eq!(dedent_len("      x", IndentUnit::Spaces(4), 4), 4);
+        assert_eq!(dedent_len("        x", IndentUnit::Tab, 8), 8);
+        assert_eq!(dedent_len("        x", IndentUnit::Tab, 4), 4);
+    }
+
+    #[test]
+    fn dedent_len_stops_at_the_text() {
+        assert_eq!(dedent_len("  x", IndentUnit::Spaces(4), 4), 2);
+        assert_eq!(dedent_len(" \tx", IndentUnit::Spaces(4), 4), 1);
+        assert_eq!(dedent_len("x", IndentUnit::Spaces(4), 4), 0);
+    }
+}
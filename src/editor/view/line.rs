@@ -2,20 +2,76 @@ use std::{fmt, ops::Range};
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+// starts a tab when tabs are shown
+const TAB_GLYPH: char = '→';
+// stands for a space while whitespace is shown
+const SPACE_GLYPH: char = '·';
+
+// The caret notation of an ASCII control character, e.g. `^[` for escape and `^?` for delete
+fn caret_notation(c: char) -> String {
+    match u8::try_from(c) {
+        Ok(0x7f) => "^?".to_string(),
+        Ok(byte) => format!("^{}", char::from(byte ^ 0x40)),
+        Err(_) => c.to_string(),
+    }
+}
+
+// How the view draws whitespace: tab stops every `tab_width` columns, and tabs with a glyph or as blank space
+#[derive(Clone, Copy)]
+pub struct DisplayStyle {
+    pub tab_width: usize,
+    pub show_tabs: bool,
+    // shows spaces and tabs with glyphs, and control characters in caret notation such as `^M`
+    pub show_whitespace: bool,
+}
+
+impl Default for DisplayStyle {
+    fn default() -> Self {
+        Self { tab_width: 4, show_tabs: false, show_whitespace: false }
+    }
+}
+
 enum GraphemeWidth {
     Half,
     Full,
+    // up to the next tab stop
+    Tab,
+    // an ASCII control character, which takes two columns in caret notation
+    Control,
 }
 
 impl GraphemeWidth {
-    const fn width(&self) -> usize {
+    // The columns taken when starting at the column
+    fn width_at(&self, col: usize, style: DisplayStyle) -> usize {
         match self {
-            Self::Half => 1,
+            Self::Control if style.show_whitespace => 2,
+            Self::Half | Self::Control => 1,
             Self::Full => 2,
+            Self::Tab => {
+                let tab_width = style.tab_width.max(1);
+                tab_width - col % tab_width
+            }
+        }
+    }
+
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Half => "half width",
+            Self::Full => "full width",
+            Self::Tab => "tab",
+            Self::Control => "control character",
         }
     }
 }
 
+// How a grapheme is drawn at its place in the line
+pub struct Rendering {
+    pub columns: usize,
+    pub kind: &'static str,
+    // what is drawn instead of the grapheme, if it can't be drawn as it is
+    pub shown_as: Option<String>,
+}
+
 pub struct TextFragment {
     grapheme: String,
     rendered_width: GraphemeWidth,
@@ -55,6 +111,20 @@ impl Line {
         value
             .graphemes(true)
             .map(|grapheme| {
+                if grapheme == "\t" {
+                    return TextFragment {
+                        grapheme: grapheme.to_string(),
+                        rendered_width: GraphemeWidth::Tab,
+                        replacement: None,
+                    };
+                }
+                if grapheme.chars().count() == 1 && grapheme.starts_with(|c: char| c.is_ascii_control()) {
+                    return TextFragment {
+                        grapheme: grapheme.to_string(),
+                        rendered_width: GraphemeWidth::Control,
+                        replacement: Some('▯'),
+                    };
+                }
                 let (replacement, rendered_width) = Self::replacement_character(grapheme)
                     .map_or(
                         {
@@ -77,7 +147,7 @@ impl Line {
             .collect()
     } 
 
-    pub fn get(&self, range: Range<usize>) -> String {
+    pub fn get(&self, range: Range<usize>, style: DisplayStyle) -> String {
         let mut res = String::new();
         let (l, r) = (range.start, range.end);
         let mut pos_ind = 0;
@@ -86,19 +156,29 @@ impl Line {
             return res;
         }
 
-        for grapheme_ind in 0..self.grapheme_count() {
+        for fragment in &self.fragments {
             if pos_ind >= r {
                 break;
             }
 
-            let width = self.fragments[grapheme_ind].rendered_width.width();
+            let width = fragment.rendered_width.width_at(pos_ind, style);
 
-            if width == 2 && (l == pos_ind + 1 || r == pos_ind + 1) {
+            if matches!(fragment.rendered_width, GraphemeWidth::Tab) {
+                // the visible part of the tab, which may be cut off on either side
+                for col in pos_ind.max(l)..(pos_ind + width).min(r) {
+                    let is_glyph = col == pos_ind && (style.show_tabs || style.show_whitespace);
+                    res.push(if is_glyph { TAB_GLYPH } else { ' ' });
+                }
+            } else if width == 2 && (l == pos_ind + 1 || r == pos_ind + 1) {
                 res.push('⋯');
+            } else if pos_ind >= l && style.show_whitespace && fragment.grapheme == " " {
+                res.push(SPACE_GLYPH);
+            } else if pos_ind >= l && style.show_whitespace && matches!(fragment.rendered_width, GraphemeWidth::Control) {
+                res.push_str(&fragment.grapheme.chars().map(caret_notation).collect::<String>());
             } else if pos_ind >= l {
-                match self.fragments[grapheme_ind].replacement {
+                match fragment.replacement {
                     Some(s) => res.push(s),
-                    None => res.push_str(&self.fragments[grapheme_ind].grapheme),
+                    None => res.push_str(&fragment.grapheme),
                 }
             }
 
@@ -108,30 +188,94 @@ impl Line {
         res
     }
 
+    // The graphemes in the range as they are stored, unlike `get` which works on rendered columns
+    pub fn get_graphemes(&self, range: Range<usize>) -> String {
+        self.fragments
+            .get(range.start.min(self.grapheme_count())..range.end.min(self.grapheme_count()))
+            .map_or_else(String::new, |fragments| {
+                fragments.iter().map(|fragment| fragment.grapheme.as_str()).collect()
+            })
+    }
+
     pub fn grapheme_count(&self) -> usize {
         self.fragments.len()
     }
 
-    pub fn width_until(&self, grapheme_index: usize) -> usize {
+    pub fn width_until(&self, grapheme_index: usize, style: DisplayStyle) -> usize {
         self.fragments
             .iter()
             .take(grapheme_index)
-            .map(|fragment| fragment.rendered_width.width())
-            .sum()
+            .fold(0, |col, fragment| col + fragment.rendered_width.width_at(col, style))
     }
 
-    pub fn width(&self) -> usize {
-        self.fragments
-            .iter()
-            .map(|fragment| fragment.rendered_width.width())
-            .sum()
+    pub fn width(&self, style: DisplayStyle) -> usize {
+        self.width_until(self.grapheme_count(), style)
+    }
+
+    pub fn rendering_at(&self, grapheme_index: usize, style: DisplayStyle) -> Option<Rendering> {
+        let fragment = self.fragments.get(grapheme_index)?;
+        let shown_as = match fragment.rendered_width {
+            GraphemeWidth::Control if style.show_whitespace => Some(caret_notation(fragment.grapheme.chars().next()?)),
+            _ => fragment.replacement.map(String::from),
+        };
+        Some(Rendering {
+            columns: fragment.rendered_width.width_at(self.width_until(grapheme_index, style), style),
+            kind: fragment.rendered_width.name(),
+            shown_as,
+        })
+    }
+
+    // Splits the line into grapheme ranges that each fit on a screen row, breaking after whitespace where
+    // possible. The first range gets `first_width` columns, the following ones `width` columns.
+    pub fn wrap(&self, first_width: usize, width: usize, style: DisplayStyle) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        let mut used = 0;
+        // tab stops count from the start of the line, not of the screen row
+        let mut col = 0;
+        let mut available = first_width.max(1);
+        // right after the last whitespace in the current range
+        let mut break_at = None;
+        let mut grapheme_ind = 0;
+
+        while grapheme_ind < self.fragments.len() {
+            let fragment = &self.fragments[grapheme_ind];
+            let is_whitespace = fragment.grapheme.trim().is_empty();
+            let fragment_width = fragment.rendered_width.width_at(col, style);
+            used += fragment_width;
+
+            if used > available && grapheme_ind > start {
+                // whitespace may hang past the edge, anything else moves to the next row
+                let end = if is_whitespace {
+                    grapheme_ind + 1
+                } else {
+                    break_at.filter(|&end| end > start).unwrap_or(grapheme_ind)
+                };
+                ranges.push(start..end);
+                col = self.width_until(end, style);
+                start = end;
+                grapheme_ind = end;
+                used = 0;
+                available = width.max(1);
+                break_at = None;
+                continue;
+            }
+
+            if is_whitespace {
+                break_at = Some(grapheme_ind + 1);
+            }
+            col += fragment_width;
+            grapheme_ind += 1;
+        }
+
+        ranges.push(start..self.fragments.len());
+        ranges
     }
 
+    #[allow(clippy::redundant_else)]
     fn replacement_character(s: &str) -> Option<char> {
         if s == " " {
             None
-        } else if s == "\t" {
-            Some(' ')
         } else if s.width() > 0 && s.trim().is_empty() {
             Some('␣')
         } else if s.width() == 0 {
@@ -188,6 +332,7 @@ impl Line {
         self.fragments = Self::str_to_fragments(&new_str);
     }
 
+    #[allow(clippy::explicit_iter_loop)]
     pub fn as_string(&self) -> String {
         let mut res = String::new();
 
@@ -204,6 +349,104 @@ impl Line {
         self.fragments = Self::str_to_fragments(&res);
     }
 
+    // Grapheme ranges of the words in the line, as found by the Unicode word boundary rules
+    fn word_spans(&self) -> Vec<Range<usize>> {
+        let text = self.as_string();
+        let mut spans = Vec::new();
+        let mut grapheme_ind = 0;
+
+        for segment in text.split_word_bounds() {
+            let len = segment.graphemes(true).count();
+            if segment.chars().any(char::is_alphanumeric) {
+                spans.push(grapheme_ind..grapheme_ind + len);
+            }
+            grapheme_ind += len;
+        }
+
+        spans
+    }
+
+    // Where the word before the grapheme index starts, skipping whitespace and punctuation in between
+    pub fn previous_word_start(&self, grapheme_index: usize) -> usize {
+        self.word_start_before(grapheme_index).unwrap_or(0)
+    }
+
+    // Where the word the grapheme index is in, or the next one, ends, or the line length if there is none
+    pub fn next_word_end(&self, grapheme_index: usize) -> usize {
+        self.word_end_from(grapheme_index).map_or(self.grapheme_count(), |end| end + 1)
+    }
+
+    pub fn word_start_before(&self, grapheme_index: usize) -> Option<usize> {
+        self.word_spans()
+            .into_iter()
+            .rev()
+            .find(|span| span.start < grapheme_index)
+            .map(|span| span.start)
+    }
+
+    // Where the first word at or after the grapheme index starts
+    pub fn word_start_from(&self, grapheme_index: usize) -> Option<usize> {
+        self.word_spans()
+            .into_iter()
+            .find(|span| span.start >= grapheme_index)
+            .map(|span| span.start)
+    }
+
+    // The last grapheme of the first word that ends at or after the grapheme index
+    pub fn word_end_from(&self, grapheme_index: usize) -> Option<usize> {
+        self.word_spans()
+            .into_iter()
+            .map(|span| span.end - 1)
+            .find(|end| *end >= grapheme_index)
+    }
+
+    // Where the whitespace at the end of the line starts, or the line length if there is none
+    pub fn trailing_whitespace_start(&self) -> usize {
+        self.fragments
+            .iter()
+            .rposition(|fragment| !fragment.grapheme.trim().is_empty())
+            .map_or(0, |ind| ind + 1)
+    }
+
+    // The index of the first grapheme that isn't whitespace, or the line length if there is none
+    pub fn first_non_blank(&self) -> usize {
+        self.fragments
+            .iter()
+            .position(|fragment| !fragment.grapheme.trim().is_empty())
+            .unwrap_or(self.fragments.len())
+    }
+
+    // The word, or run of whitespace or punctuation, the grapheme index is in
+    pub fn segment_at(&self, grapheme_index: usize) -> Range<usize> {
+        let text = self.as_string();
+        let mut start = 0;
+
+        for segment in text.split_word_bounds() {
+            let end = start + segment.graphemes(true).count();
+            if grapheme_index < end {
+                return start..end;
+            }
+            start = end;
+        }
+        start..start
+    }
+
+    pub fn grapheme_at(&self, grapheme_index: usize) -> Option<&str> {
+        self.fragments.get(grapheme_index).map(|fragment| fragment.grapheme.as_str())
+    }
+
+    pub fn delete_range(&mut self, range: Range<usize>) {
+        let new_str: String = self
+            .fragments
+            .iter()
+            .enumerate()
+            .filter(|(ind, _)| !range.contains(ind))
+            .map(|(_, fragment)| fragment.grapheme.as_str())
+            .collect();
+
+        self.fragments = Self::str_to_fragments(&new_str);
+    }
+
     pub fn split(&mut self, grapheme_index: usize) -> Vec<TextFragment> {
         self.fragments.split_off(grapheme_index)
     }    
@@ -0,0 +1,163 @@
+use std::ops::Range;
+
+use super::line::{DisplayStyle, Line};
+
+// Comment markers whose lines get reflowed as one paragraph, with the marker repeated on every line
+const COMMENT_MARKERS: [char; 3] = ['/', '#', '>'];
+
+// Splits the line into its indentation along with any comment marker and the space after it, and the text
+pub fn split_prefix(line: &str) -> (&str, &str) {
+    let content = line.trim_start();
+    let marker = match content.chars().next() {
+        Some('/') if content.starts_with("//") => content.trim_start_matches(['/', '!']),
+        Some(c) if COMMENT_MARKERS.contains(&c) && c != '/' => content.trim_start_matches([c, ' ']),
+        _ => content,
+    };
+    let text = marker.trim_start();
+    line.split_at(line.len() - text.len())
+}
+
+// The comment marker without the whitespace around it, which has to match for lines to share a paragraph
+fn marker(line: &str) -> &str {
+    split_prefix(line).0.trim()
+}
+
+// The columns the text takes, with tabs up to the next tab stop
+fn width(text: &str, style: DisplayStyle) -> usize {
+    Line::from(text).width(style)
+}
+
+// Whether the line has text beyond its indentation and comment marker
+pub fn has_text(line: &str) -> bool {
+    !split_prefix(line).1.trim().is_empty()
+}
+
+pub fn same_paragraph(line: &str, other: &str) -> bool {
+    has_text(line) && has_text(other) && marker(line) == marker(other)
+}
+
+// Fills each paragraph of the lines up to the column. The first line of a paragraph keeps its prefix,
+// the others get the prefix of its second line, so hanging indentation survives.
+pub fn reflow(lines: &[String], column: usize, style: DisplayStyle) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut start = 0;
+
+    while start < lines.len() {
+        let end = (start + 1..lines.len())
+            .find(|&ind| !same_paragraph(&lines[ind - 1], &lines[ind]))
+            .unwrap_or(lines.len());
+        let paragraph = &lines[start..end];
+
+        if has_text(&paragraph[0]) {
+            result.extend(fill(paragraph, column, style));
+        } else {
+            result.extend(paragraph.iter().cloned());
+        }
+        start = end;
+    }
+    result
+}
+
+fn fill(paragraph: &[String], column: usize, style: DisplayStyle) -> Vec<String> {
+    let first_prefix = line_prefix(&paragraph[0]);
+    let rest_prefix = paragraph.get(1).map_or_else(|| first_prefix.clone(), |line| line_prefix(line));
+    let words = paragraph.iter().flat_map(|line| split_prefix(line).1.split_whitespace());
+
+    let mut lines = Vec::new();
+    let mut current = first_prefix;
+    let mut is_empty = true;
+    for word in words {
+        if !is_empty && width(&current, style) + 1 + width(word, style) > column {
+            lines.push(std::mem::replace(&mut current, rest_prefix.clone()));
+            is_empty = true;
+        }
+        if !is_empty || !current.trim().is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+        is_empty = false;
+    }
+    lines.push(current);
+    lines
+}
+
+// What starts every line of a paragraph: the indentation, and any comment marker without the space after it
+fn line_prefix(line: &str) -> String {
+    let prefix = split_prefix(line).0;
+    if prefix.trim().is_empty() {
+        prefix.to_string()
+    } else {
+        prefix.trim_end().to_string()
+    }
+}
+
+// Where to break a line that runs past the column while typing: the byte range of the last whitespace
+// before `end` that starts within the column, after the prefix. A word longer than the column stays put.
+pub fn break_point(line: &str, end: usize, column: usize, style: DisplayStyle) -> Option<Range<usize>> {
+    let prefix_len = split_prefix(line).0.len();
+    let text = line.get(prefix_len..end)?;
+
+    let mut best = None;
+    let mut run_start = None;
+    for (ind, c) in text.char_indices() {
+        let ind = prefix_len + ind;
+        if c.is_whitespace() {
+            if run_start.is_none() && width(&line[..ind], style) <= column {
+                run_start = Some(ind);
+            }
+        } else if let Some(start) = run_start.take() {
+            best = Some(start..ind);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill_lines(lines: &[&str], column: usize, tab_width: usize) -> Vec<String> {
+        let lines: Vec<String> = lines.iter().map(ToString::to_string).collect();
+        fill(&lines, column, DisplayStyle { tab_width, ..DisplayStyle::default() })
+    }
+
+    #[test]
+    fn fill_breaks_between_words() {
+        assert_eq!(fill_lines(&["one two three four"], 10, 4), ["one two", "three four"]);
+        assert_eq!(fill_lines(&["one", "two", "three"], 80, 4), ["one two three"]);
+    }
+
+    #[test]
+    fn fill_keeps_long_words_whole() {
+        assert_eq!(fill_lines(&["abcdefghijkl x"], 5, 4), ["abcdefghijkl", "x"]);
+    }
+
+    #[test]
+    fn fill_repeats_the_prefix() {
+        assert_eq!(fill_lines(&["// alpha beta gamma"], 12, 4), ["// alpha", "// beta", "// gamma"]);
+        assert_eq!(fill_lines(&["- item one two", "  three"], 10, 4), ["- item one", "  two", "  three"]);
+    }
+
+    #[test]
+    fn fill_counts_tabs_to_the_tab_stop() {
+        assert_eq!(fill_lines(&["\tone two"], 10, 4), ["\tone", "\ttwo"]);
+        assert_eq!(fill_lines(&["\tone two"], 10, 2), ["\tone two"]);
+    }
+
+    #[test]
+    fn break_point_finds_the_last_gap_within_the_column() {
+        let style = DisplayStyle::default();
+        assert_eq!(break_point("hello world foo", 15, 12, style), Some(11..12));
+        assert_eq!(break_point("hello world foo", 15, 10, style), Some(5..6));
+        assert_eq!(break_point("hello   world", 13, 10, style), Some(5..8));
+        assert_eq!(break_point("    foo bar", 11, 8, style), Some(7..8));
+    }
+
+    #[test]
+    fn break_point_leaves_long_words_and_the_prefix_alone() {
+        let style = DisplayStyle::default();
+        assert_eq!(break_point("superlongword next", 18, 5, style), None);
+        assert_eq!(break_point("    foo", 7, 2, style), None);
+        assert_eq!(break_point("// foo", 6, 2, style), None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::str::FromStr;
+
+// Which lines lose their trailing whitespace when the buffer is saved
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrimOnSave {
+    #[default]
+    Off,
+    // lines edited since the file was loaded or last saved
+    Modified,
+    All,
+}
+
+impl TrimOnSave {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Modified => "modified",
+            Self::All => "all",
+        }
+    }
+}
+
+impl FromStr for TrimOnSave {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "off" | "false" | "no" => Ok(Self::Off),
+            "modified" => Ok(Self::Modified),
+            "all" | "on" | "true" | "yes" => Ok(Self::All),
+            _ => Err(format!("expected off, modified or all, got {value:?}")),
+        }
+    }
+}
+
+// The line with its indentation redone using as many tabs as fit, or spaces only, keeping its width
+pub fn retab(text: &str, tab_width: usize, to_tabs: bool) -> String {
+    let content = text.trim_start_matches([' ', '\t']);
+    let width = text[..text.len() - content.len()].chars().fold(0, |width, c| {
+        if c == '\t' {
+            width + tab_width - width % tab_width
+        } else {
+            width + 1
+        }
+    });
+
+    let indentation = if to_tabs {
+        format!("{}{}", "\t".repeat(width / tab_width), " ".repeat(width % tab_width))
+    } else {
+        " ".repeat(width)
+    };
+    format!("{indentation}{content}")
+}
+
+pub fn is_blank(text: &str) -> bool {
+    text.trim().is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retab_to_spaces_keeps_the_width() {
+        assert_eq!(retab("\t\tx", 4, false), "        x");
+        assert_eq!(retab(" \tx", 4, false), "    x");
+        assert_eq!(retab("  \t  ", 4, false), "      ");
+    }
+
+    #[test]
+    fn retab_to_tabs_uses_as_many_tabs_as_fit() {
+        assert_eq!(retab("        x", 4, true), "\t\tx");
+        assert_eq!(retab("      x", 4, true), "\t  x");
+        assert_eq!(retab(" \tx", 4, true), "\tx");
+        assert_eq!(retab("        x", 8, true), "\tx");
+    }
+
+    #[test]
+    fn retab_leaves_the_rest_of_the_line_alone() {
+        assert_eq!(retab("x \t y  ", 4, true), "x \t y  ");
+        assert_eq!(retab("  x\t y", 2, true), "\tx\t y");
+    }
+}
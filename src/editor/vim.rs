@@ -0,0 +1,571 @@
+use std::mem;
+
+use crossterm::event::KeyCode;
+
+use super::{
+    command::{keymap::KeyChord, moves::MoveCommand},
+    position::Location,
+    view::View,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+    // the ":" command line is open
+    Command,
+}
+
+impl VimMode {
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Normal => "NORMAL",
+            Self::Insert => "INSERT",
+            Self::Visual => "VISUAL",
+            Self::Command => "COMMAND",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    FirstLine,
+    LastLine,
+}
+
+// How much of the text a motion covers when it follows an operator
+enum MotionKind {
+    Exclusive,
+    Inclusive,
+    Linewise,
+}
+
+impl Motion {
+    const fn from_char(c: char) -> Option<Self> {
+        match c {
+            'h' => Some(Self::Left),
+            'l' | ' ' => Some(Self::Right),
+            'k' => Some(Self::Up),
+            'j' => Some(Self::Down),
+            'w' => Some(Self::WordForward),
+            'b' => Some(Self::WordBackward),
+            'e' => Some(Self::WordEnd),
+            '0' => Some(Self::LineStart),
+            '^' => Some(Self::FirstNonBlank),
+            '$' => Some(Self::LineEnd),
+            'G' => Some(Self::LastLine),
+            _ => None,
+        }
+    }
+
+    const fn kind(self) -> MotionKind {
+        match self {
+            Self::Up | Self::Down | Self::FirstLine | Self::LastLine => MotionKind::Linewise,
+            Self::WordEnd | Self::LineEnd => MotionKind::Inclusive,
+            _ => MotionKind::Exclusive,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    const fn from_char(c: char) -> Option<Self> {
+        match c {
+            'd' => Some(Self::Delete),
+            'c' => Some(Self::Change),
+            'y' => Some(Self::Yank),
+            _ => None,
+        }
+    }
+}
+
+// The start of a command that needs more keys
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum Pending {
+    #[default]
+    None,
+    G,
+    Operator(Operator),
+    OperatorG(Operator),
+    // whether the object is the inner one, like `iw`, or includes its surroundings, like `aw`
+    TextObject(Operator, bool),
+}
+
+pub enum VimResponse {
+    Handled,
+    // the key isn't a modal command, so it should do what it does without modal editing
+    PassThrough,
+    CommandLine,
+    // the keys of the last change, to be pressed again
+    Replay(Vec<KeyChord>),
+}
+
+#[derive(Default)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+const fn next(location: Location) -> Location {
+    Location {
+        row: location.row,
+        grapheme_index: location.grapheme_index + 1,
+    }
+}
+
+// Vim-style modal editing, sitting in front of the view's own commands
+#[derive(Default)]
+pub struct Vim {
+    mode: VimMode,
+    pending: Pending,
+    count: Option<usize>,
+    operator_count: Option<usize>,
+    visual_anchor: Location,
+    register: Register,
+    // the keys of the command being typed, and of the last one that changed the text, for `.`
+    recording: Vec<KeyChord>,
+    last_change: Vec<KeyChord>,
+    // changes made from visual mode depend on the selection, so they can't be repeated
+    insert_is_repeatable: bool,
+}
+
+impl Vim {
+    pub const fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    pub fn leave_command_mode(&mut self) {
+        if self.mode == VimMode::Command {
+            self.mode = VimMode::Normal;
+        }
+    }
+
+    // Shows the selection while in visual mode
+    pub fn sync_view(&self, view: &mut View) {
+        let highlight = (self.mode == VimMode::Visual).then(|| self.selection(view));
+        view.set_highlight(highlight);
+    }
+
+    pub fn handle_key(&mut self, chord: KeyChord, view: &mut View) -> VimResponse {
+        match self.mode {
+            VimMode::Insert => {
+                self.recording.push(chord);
+                if chord != KeyChord::ESCAPE {
+                    return VimResponse::PassThrough;
+                }
+
+                let recording = mem::take(&mut self.recording);
+                if self.insert_is_repeatable {
+                    self.last_change = recording;
+                }
+                self.mode = VimMode::Normal;
+                // leaving insert mode puts the caret back on the last inserted character
+                let location = view.location();
+                view.set_location(Location {
+                    grapheme_index: location.grapheme_index.saturating_sub(1),
+                    ..location
+                });
+                VimResponse::Handled
+            }
+            VimMode::Command => VimResponse::PassThrough,
+            VimMode::Normal | VimMode::Visual => {
+                if chord == KeyChord::ESCAPE {
+                    self.reset();
+                    self.mode = VimMode::Normal;
+                    return VimResponse::Handled;
+                }
+
+                let key = chord.text().or_else(|| match chord {
+                    _ if chord == KeyChord::plain(KeyCode::Enter) => Some('j'),
+                    _ if chord == KeyChord::plain(KeyCode::Backspace) => Some('h'),
+                    _ if chord == KeyChord::plain(KeyCode::Delete) => Some('x'),
+                    _ => None,
+                });
+                let Some(key) = key else {
+                    // shortcuts and keys like the arrows work as they do without modal editing
+                    self.reset();
+                    return VimResponse::PassThrough;
+                };
+
+                if self.pending == Pending::None && self.count.is_none() {
+                    self.recording.clear();
+                    self.insert_is_repeatable = self.mode == VimMode::Normal;
+                }
+                self.recording.push(chord);
+                self.normal_key(key, view)
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pending = Pending::None;
+        self.count = None;
+        self.operator_count = None;
+    }
+
+    // The count typed before the operator times the one typed after it, e.g. 6 for `2d3w`
+    fn explicit_count(&self) -> Option<usize> {
+        match (self.operator_count, self.count) {
+            (None, None) => None,
+            (first, second) => Some(first.unwrap_or(1).saturating_mul(second.unwrap_or(1))),
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.explicit_count().unwrap_or(1)
+    }
+
+    fn normal_key(&mut self, key: char, view: &mut View) -> VimResponse {
+        if let Some(digit) = key.to_digit(10).filter(|digit| *digit != 0 || self.count.is_some()) {
+            let count = self.count.unwrap_or(0).saturating_mul(10).saturating_add(digit as usize);
+            self.count = Some(count);
+            return VimResponse::Handled;
+        }
+
+        let mut response = VimResponse::Handled;
+        match mem::take(&mut self.pending) {
+            Pending::None => response = self.command_key(key, view),
+            Pending::G if key == 'g' => self.move_caret(Motion::FirstLine, view),
+            Pending::Operator(operator) if Operator::from_char(key) == Some(operator) => {
+                self.operate_on_lines(operator, view);
+            }
+            Pending::Operator(operator) if key == 'i' || key == 'a' => {
+                self.pending = Pending::TextObject(operator, key == 'i');
+            }
+            Pending::Operator(operator) if key == 'g' => self.pending = Pending::OperatorG(operator),
+            Pending::Operator(operator) => {
+                if let Some(motion) = Motion::from_char(key) {
+                    self.operate_on_motion(operator, motion, view);
+                }
+            }
+            Pending::OperatorG(operator) if key == 'g' => self.operate_on_motion(operator, Motion::FirstLine, view),
+            Pending::TextObject(operator, inner) => {
+                if let Some(range) = Self::text_object(key, inner, view) {
+                    self.operate(operator, range, false, view);
+                    self.record_change(operator);
+                }
+            }
+            Pending::G | Pending::OperatorG(_) => {}
+        }
+
+        if self.pending == Pending::None {
+            self.count = None;
+            self.operator_count = None;
+        }
+        response
+    }
+
+    // Handles a key that starts a command
+    fn command_key(&mut self, key: char, view: &mut View) -> VimResponse {
+        if let Some(motion) = Motion::from_char(key) {
+            self.move_caret(motion, view);
+            return VimResponse::Handled;
+        }
+        if self.mode == VimMode::Visual {
+            self.visual_key(key, view);
+            return VimResponse::Handled;
+        }
+
+        match key {
+            'g' => self.pending = Pending::G,
+            'd' | 'c' | 'y' => {
+                self.pending = Operator::from_char(key).map_or(Pending::None, Pending::Operator);
+                self.operator_count = self.count.take();
+            }
+            'x' => self.operate_on_motion(Operator::Delete, Motion::Right, view),
+            'D' => self.operate_on_motion(Operator::Delete, Motion::LineEnd, view),
+            'C' => self.operate_on_motion(Operator::Change, Motion::LineEnd, view),
+            'i' => self.mode = VimMode::Insert,
+            'a' => {
+                let location = view.location();
+                if location.grapheme_index < view.grapheme_count(location.row) {
+                    view.set_location(next(location));
+                }
+                self.mode = VimMode::Insert;
+            }
+            'I' => {
+                self.move_caret(Motion::FirstNonBlank, view);
+                self.mode = VimMode::Insert;
+            }
+            'A' => {
+                self.move_caret(Motion::LineEnd, view);
+                self.mode = VimMode::Insert;
+            }
+            'o' | 'O' => self.open_line(key == 'o', view),
+            'v' => {
+                self.visual_anchor = view.location();
+                self.mode = VimMode::Visual;
+            }
+            'p' | 'P' => self.put(key == 'p', view),
+            '.' => {
+                let keys = self.last_change.repeat(self.count());
+                return VimResponse::Replay(keys);
+            }
+            ':' => {
+                self.mode = VimMode::Command;
+                return VimResponse::CommandLine;
+            }
+            _ => {}
+        }
+        VimResponse::Handled
+    }
+
+    fn visual_key(&mut self, key: char, view: &mut View) {
+        let operator = match key {
+            'd' | 'x' => Operator::Delete,
+            'c' => Operator::Change,
+            'y' => Operator::Yank,
+            'v' => {
+                self.mode = VimMode::Normal;
+                return;
+            }
+            'o' => {
+                // jump to the other end of the selection
+                let location = view.location();
+                view.set_location(self.visual_anchor);
+                self.visual_anchor = location;
+                return;
+            }
+            _ => return,
+        };
+
+        let selection = self.selection(view);
+        self.mode = VimMode::Normal;
+        self.operate(operator, selection, false, view);
+    }
+
+    // From the start of the selection up to and including the grapheme under its end
+    fn selection(&self, view: &View) -> (Location, Location) {
+        let caret = view.location();
+        let (start, end) = (self.visual_anchor.min(caret), self.visual_anchor.max(caret));
+        (start, next(end))
+    }
+
+    fn move_caret(&self, motion: Motion, view: &mut View) {
+        match motion {
+            // moving through lines keeps the column the caret came from
+            Motion::Up | Motion::Down => {
+                let command = if motion == Motion::Up { MoveCommand::Up } else { MoveCommand::Down };
+                for _ in 0..self.count() {
+                    view.handle_move_command(command);
+                }
+            }
+            _ => view.set_location(self.motion_target(motion, view)),
+        }
+    }
+
+    fn motion_target(&self, motion: Motion, view: &View) -> Location {
+        let from = view.location();
+        let count = self.count();
+        let last_row = view.line_count().saturating_sub(1);
+        let line_start = |row: usize| Location { row, grapheme_index: view.first_non_blank(row) };
+        let repeat = |step: fn(&View, Location) -> Location| (0..count).fold(from, |location, _| step(view, location));
+
+        match motion {
+            Motion::Left => Location {
+                grapheme_index: from.grapheme_index.saturating_sub(count),
+                ..from
+            },
+            Motion::Right => Location {
+                grapheme_index: from.grapheme_index.saturating_add(count).min(view.grapheme_count(from.row)),
+                ..from
+            },
+            Motion::Up => Location { row: from.row.saturating_sub(count), ..from },
+            Motion::Down => Location { row: from.row.saturating_add(count).min(last_row), ..from },
+            Motion::WordForward => repeat(View::word_forward),
+            Motion::WordBackward => repeat(View::word_backward),
+            Motion::WordEnd => repeat(View::word_end),
+            Motion::LineStart => Location { grapheme_index: 0, ..from },
+            Motion::FirstNonBlank => line_start(from.row),
+            Motion::LineEnd => Location { grapheme_index: view.grapheme_count(from.row), ..from },
+            Motion::FirstLine => line_start(self.explicit_count().map_or(0, |line| line - 1).min(last_row)),
+            Motion::LastLine => line_start(self.explicit_count().map_or(last_row, |line| line - 1).min(last_row)),
+        }
+    }
+
+    fn operate_on_motion(&mut self, operator: Operator, motion: Motion, view: &mut View) {
+        let from = view.location();
+
+        // like in vim, `cw` on a word changes up to the end of the word rather than to the next one
+        let on_word = view.text_between(from, next(from)).chars().any(char::is_alphanumeric);
+        if operator == Operator::Change && motion == Motion::WordForward && on_word {
+            let range = if self.count() == 1 {
+                (from, view.segment_at(from).1)
+            } else {
+                (from, next(self.motion_target(Motion::WordEnd, view)))
+            };
+            self.operate(operator, range, false, view);
+            return;
+        }
+
+        let target = self.motion_target(motion, view);
+        let (start, mut end) = (from.min(target), from.max(target));
+        match motion.kind() {
+            MotionKind::Linewise => {
+                self.operate(operator, (start, end), true, view);
+                self.record_change(operator);
+                return;
+            }
+            MotionKind::Inclusive => end = next(end),
+            // a word motion that would cross into the next line stops at the end of this one
+            MotionKind::Exclusive if motion == Motion::WordForward && end.row > start.row => {
+                end = Location {
+                    row: start.row,
+                    grapheme_index: view.grapheme_count(start.row),
+                };
+            }
+            MotionKind::Exclusive => {}
+        }
+
+        self.operate(operator, (start, end), false, view);
+        self.record_change(operator);
+    }
+
+    fn operate_on_lines(&mut self, operator: Operator, view: &mut View) {
+        let row = view.location().row;
+        let last_row = row.saturating_add(self.count() - 1);
+        let start = Location { row, grapheme_index: 0 };
+        let end = Location { row: last_row, grapheme_index: 0 };
+
+        self.operate(operator, (start, end), true, view);
+        self.record_change(operator);
+    }
+
+    fn operate(&mut self, operator: Operator, (start, end): (Location, Location), linewise: bool, view: &mut View) {
+        if linewise {
+            let end_row = end.row.min(view.line_count().saturating_sub(1));
+            let rows = start.row..end_row + 1;
+            self.register = Register {
+                text: view.lines_text(rows.clone()),
+                linewise: true,
+            };
+
+            match operator {
+                Operator::Delete => view.delete_lines(rows),
+                Operator::Change => {
+                    // the lines are replaced by a single empty one to type into
+                    let end = Location { row: end_row, grapheme_index: view.grapheme_count(end_row) };
+                    view.delete_between(Location { row: start.row, grapheme_index: 0 }, end);
+                    self.mode = VimMode::Insert;
+                }
+                Operator::Yank => view.set_location(Location { row: start.row, ..view.location() }),
+            }
+        } else {
+            self.register = Register {
+                text: view.text_between(start, end),
+                linewise: false,
+            };
+
+            match operator {
+                Operator::Delete => view.delete_between(start, end),
+                Operator::Change => {
+                    view.delete_between(start, end);
+                    self.mode = VimMode::Insert;
+                }
+                Operator::Yank => view.set_location(start),
+            }
+        }
+    }
+
+    // Remembers the command for `.` if it changed the text. A change that entered insert mode
+    // is remembered once insert mode is left, along with what was typed.
+    fn record_change(&mut self, operator: Operator) {
+        if operator == Operator::Delete {
+            self.last_change = self.recording.clone();
+        }
+    }
+
+    fn text_object(key: char, inner: bool, view: &View) -> Option<(Location, Location)> {
+        let at = view.location();
+        let pair = |open: &str, close: &str| {
+            let (open, close) = view.enclosing_pair(at, open, close)?;
+            Some(if inner { (next(open), close) } else { (open, next(close)) })
+        };
+
+        match key {
+            'w' => {
+                let (start, end) = view.segment_at(at);
+                if inner {
+                    return Some((start, end));
+                }
+                // `aw` takes the whitespace after the word along
+                let (_, after) = view.segment_at(end);
+                let followed_by_space = after > end && view.text_between(end, after).trim().is_empty();
+                Some((start, if followed_by_space { after } else { end }))
+            }
+            '(' | ')' | 'b' => pair("(", ")"),
+            '[' | ']' => pair("[", "]"),
+            '{' | '}' | 'B' => pair("{", "}"),
+            '<' | '>' => pair("<", ">"),
+            '"' | '\'' | '`' => {
+                let (open, close) = view.enclosing_quotes(at, &key.to_string())?;
+                Some(if inner { (next(open), close) } else { (open, next(close)) })
+            }
+            _ => None,
+        }
+    }
+
+    fn open_line(&mut self, below: bool, view: &mut View) {
+        let row = view.location().row;
+        if below {
+            view.insert_text(Location { row, grapheme_index: view.grapheme_count(row) }, "\n");
+        } else {
+            view.insert_text(Location { row, grapheme_index: 0 }, "\n");
+            view.set_location(Location { row, grapheme_index: 0 });
+        }
+        self.mode = VimMode::Insert;
+    }
+
+    fn put(&mut self, after: bool, view: &mut View) {
+        if self.register.text.is_empty() {
+            return;
+        }
+
+        let text = self.register.text.repeat(self.count());
+        let at = view.location();
+        if self.register.linewise {
+            let row = if after { at.row + 1 } else { at.row };
+            let line_count = view.line_count();
+            if row >= line_count && line_count > 0 {
+                // below the last line, which doesn't end with a newline of its own
+                let end = Location { row: line_count - 1, grapheme_index: view.grapheme_count(line_count - 1) };
+                view.insert_text(end, &format!("\n{}", text.strip_suffix('\n').unwrap_or(&text)));
+            } else {
+                view.insert_text(Location { row, grapheme_index: 0 }, &text);
+            }
+            view.set_location(Location { row, grapheme_index: view.first_non_blank(row) });
+        } else {
+            let grapheme_index = if after {
+                (at.grapheme_index + 1).min(view.grapheme_count(at.row))
+            } else {
+                at.grapheme_index
+            };
+            view.insert_text(Location { row: at.row, grapheme_index }, &text);
+            // the caret ends up on the last pasted character
+            let end = view.location();
+            view.set_location(Location {
+                grapheme_index: end.grapheme_index.saturating_sub(1),
+                ..end
+            });
+        }
+        self.last_change = self.recording.clone();
+    }
+}